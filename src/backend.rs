@@ -2,14 +2,21 @@ use log::info;
 use std::sync::mpsc;
 use uuid::Uuid;
 
-use crate::data_source::DataSource;
+use crate::comps::*;
+use crate::data_source::{DataSource, GameState};
+use crate::ledger::*;
+use crate::limits::*;
+use crate::settlement::*;
+use crate::side_bets::*;
 use crate::types::*;
 use crate::utils::*;
+use crate::wallet::*;
 
 pub enum Resource {
     Game,
     Player,
     HandAction,
+    Account,
 }
 
 // @todo: This needs tho have a header that includes the game_id and potentially the hand or
@@ -17,10 +24,27 @@ pub enum Resource {
 pub enum Response {
     StatusOk,
     AddResource(Resource, Uuid),
-    TableList(Vec<Uuid>), //< I think maybe this should be a json object or something?
+    TableList(Vec<(Uuid, TableRules)>), //< I think maybe this should be a json object or something?
     Hand(Uuid),
+    Seat(Vec<Uuid>),
     HandValue(u8),
     HandOutcome(Option<Outcome>),
+    Settlement(Option<Settlement>),
+    Round(Uuid),
+    InsuranceOffered,
+    InsuranceOutcome(Option<InsuranceOutcome>),
+    SideBetOutcomes(Vec<(SideBetKind, SideBetResult)>),
+    ActionRejected(ActionResolutionError),
+    BetRejected(BetError),
+    JoinRejected(JoinError),
+    DepositRejected(DepositError),
+    Limits(PlayerLimits),
+    CompPoints(u32),
+    CompHistory(Vec<(Uuid /*round_id*/, Comp)>),
+    Balance(Account),
+    Transactions(Vec<Transaction>),
+    LedgerEntries(Vec<LedgerEntry>),
+    LedgerVerified(Result<(), LedgerError>),
     Failed,
 }
 
@@ -32,8 +56,29 @@ pub enum Message {
     //@todo: This shouldnt be a client message, needs to get handled in the simulation step.
     //StartGame(Uuid /*game_id*/),
     GetCurrentHand(Uuid /*game_id*/),
+    GetSeatHands(Uuid /*hand_id*/),
     GetHandValue(Uuid /*hand_id*/),
     GetHandOutcome(Uuid /*hand_id*/),
+    GetCurrentRound(Uuid /*game_id*/),
+    GetRoundOutcome(Uuid /*hand_id*/, Uuid /*round_id*/),
+    GetSettlement(Uuid /*hand_id*/, Uuid /*round_id*/),
+    AddInsurance(Uuid /*hand_id*/, Insurance),
+    GetInsuranceOutcome(Uuid /*hand_id*/),
+    AddSideBet(Uuid /*hand_id*/, SideBetKind, u32),
+    GetSideBetOutcomes(Uuid /*hand_id*/, Uuid /*round_id*/),
+    CreateAccount(u32 /*deposit*/),
+    Deposit(Uuid /*account_id*/, u32),
+    JoinTable(Uuid /*game_id*/, Uuid /*account_id*/),
+    PlaceBet(Uuid /*hand_id*/, u32),
+    GetBalance(Uuid /*account_id*/),
+    GetTransactions(Uuid /*account_id*/),
+    GetLedgerEntries(Uuid /*round_id*/),
+    VerifyLedger,
+    SetLimit(Uuid /*account_id*/, Limit),
+    Exclude(Uuid /*account_id*/, chrono::Duration),
+    GetLimits(Uuid /*account_id*/),
+    GetCompPoints(Uuid /*account_id*/),
+    GetCompHistory(Uuid /*account_id*/),
 }
 
 pub struct MessagePacket {
@@ -56,19 +101,21 @@ pub fn process(rx: &mpsc::Receiver<MessagePacket>, ds: &mut DataSource) {
                 let player_id = ds.add_player(game_id);
                 Response::AddResource(Resource::Player, player_id)
             }
-            Message::AddHandAction(_hand_id, _action) => {
+            Message::AddHandAction(hand_id, action) => {
                 info!("server: AddHandAction");
-                //@todo: This should go into some kinda temp buffer that then gets
-                //  processed by the simulation step.  I guess that means that
-                //  there shouldnt be a response here, maybe just an ack?
-                //ds.add_action(hand_id, action);
-                //let new_uuid = Uuid::new_v4();
-                //Response::AddResource(Resource::HandAction, new_uuid)
-                Response::Failed
+                // The action is queued up and played out by the simulation step.
+                match ds.add_action(hand_id, action) {
+                    Ok(()) => Response::AddResource(Resource::HandAction, Uuid::new_v4()),
+                    Err(e) => Response::ActionRejected(e),
+                }
             }
             Message::GetTableList => {
                 //@todo: Should this only respond with open tables?
-                let tables: Vec<Uuid> = ds.decks.keys().cloned().collect();
+                let tables: Vec<(Uuid, TableRules)> = ds
+                    .shoes
+                    .keys()
+                    .map(|game_id| (*game_id, ds.rules.get(game_id).cloned().unwrap_or_default()))
+                    .collect();
                 Response::TableList(tables)
             }
             //@todo: Starting the game should not be a client side message
@@ -79,12 +126,23 @@ pub fn process(rx: &mpsc::Receiver<MessagePacket>, ds: &mut DataSource) {
             }*/
             Message::GetCurrentHand(game_id) => {
                 info!("server: GetCurrentHand");
-                get_active_hand(game_id, &ds.active_hands, &ds.hands)
-                    .map_or(Response::Failed, Response::Hand)
+                // Nobody has the turn while the table is waiting on insurance decisions.
+                if let Some(GameState::Insurance) = ds.get_game_state(game_id) {
+                    Response::InsuranceOffered
+                } else {
+                    get_active_hand(game_id, &ds.active_hands, &ds.hands)
+                        .map_or(Response::Failed, Response::Hand)
+                }
+            }
+            Message::GetSeatHands(hand_id) => {
+                info!("server: GetSeatHands");
+                Response::Seat(ds.get_seat_hands(hand_id))
             }
             Message::GetHandValue(hand_id) => {
                 info!("server: GetHandValue");
-                let hand_value = get_hand_value(hand_id, &ds.hands, &ds.allocations, &ds.decks);
+                // Players only ever get to see the cards that are face up.
+                let hand_value =
+                    get_visible_hand_value(hand_id, &ds.hands, &ds.allocations, &ds.shoes);
                 Response::HandValue(hand_value)
             }
             Message::GetHandOutcome(hand_id) => {
@@ -92,6 +150,118 @@ pub fn process(rx: &mpsc::Receiver<MessagePacket>, ds: &mut DataSource) {
                 let hand_outcome = get_hand_outcome(hand_id, &ds.outcomes);
                 Response::HandOutcome(hand_outcome)
             }
+            Message::GetCurrentRound(game_id) => {
+                info!("server: GetCurrentRound");
+                ds.get_current_round(game_id)
+                    .map_or(Response::Failed, Response::Round)
+            }
+            Message::GetRoundOutcome(hand_id, round_id) => {
+                info!("server: GetRoundOutcome");
+                let hand_outcome = get_round_outcome(hand_id, round_id, &ds.outcomes);
+                Response::HandOutcome(hand_outcome)
+            }
+            Message::GetSettlement(hand_id, round_id) => {
+                info!("server: GetSettlement");
+                let settlement = get_settlement(hand_id, round_id, &ds.settlements);
+                Response::Settlement(settlement)
+            }
+            Message::AddInsurance(hand_id, decision) => {
+                info!("server: AddInsurance");
+                if ds.add_insurance(hand_id, decision) {
+                    Response::StatusOk
+                } else {
+                    Response::Failed
+                }
+            }
+            Message::GetInsuranceOutcome(hand_id) => {
+                info!("server: GetInsuranceOutcome");
+                let insurance_outcome = get_insurance_outcome(hand_id, &ds.insurance_outcomes);
+                Response::InsuranceOutcome(insurance_outcome)
+            }
+            Message::AddSideBet(hand_id, kind, amount) => {
+                info!("server: AddSideBet");
+                if ds.place_side_bet(hand_id, kind, amount) {
+                    Response::StatusOk
+                } else {
+                    Response::Failed
+                }
+            }
+            Message::GetSideBetOutcomes(hand_id, round_id) => {
+                info!("server: GetSideBetOutcomes");
+                let outcomes = get_side_bet_outcomes(hand_id, round_id, &ds.side_bet_outcomes);
+                Response::SideBetOutcomes(outcomes)
+            }
+            Message::CreateAccount(deposit) => {
+                info!("server: CreateAccount");
+                let account_id = ds.create_account(deposit);
+                Response::AddResource(Resource::Account, account_id)
+            }
+            Message::Deposit(account_id, amount) => {
+                info!("server: Deposit");
+                match ds.deposit(account_id, amount) {
+                    Ok(()) => Response::StatusOk,
+                    Err(e) => Response::DepositRejected(e),
+                }
+            }
+            Message::JoinTable(game_id, account_id) => {
+                info!("server: JoinTable");
+                match ds.join_table(game_id, account_id) {
+                    Ok(hand_id) => Response::AddResource(Resource::Player, hand_id),
+                    Err(e) => Response::JoinRejected(e),
+                }
+            }
+            Message::PlaceBet(hand_id, amount) => {
+                info!("server: PlaceBet");
+                match ds.place_bet(hand_id, amount) {
+                    Ok(()) => Response::StatusOk,
+                    Err(e) => Response::BetRejected(e),
+                }
+            }
+            Message::GetBalance(account_id) => {
+                info!("server: GetBalance");
+                ds.get_balance(account_id)
+                    .map_or(Response::Failed, Response::Balance)
+            }
+            Message::GetTransactions(account_id) => {
+                info!("server: GetTransactions");
+                Response::Transactions(get_transactions(account_id, &ds.transactions))
+            }
+            Message::GetLedgerEntries(round_id) => {
+                info!("server: GetLedgerEntries");
+                Response::LedgerEntries(get_ledger_entries(round_id, &ds.ledger))
+            }
+            Message::VerifyLedger => {
+                info!("server: VerifyLedger");
+                Response::LedgerVerified(ds.verify_ledger())
+            }
+            Message::SetLimit(account_id, limit) => {
+                info!("server: SetLimit");
+                if ds.set_limit(account_id, limit) {
+                    Response::StatusOk
+                } else {
+                    Response::Failed
+                }
+            }
+            Message::Exclude(account_id, duration) => {
+                info!("server: Exclude");
+                if ds.exclude(account_id, duration) {
+                    Response::StatusOk
+                } else {
+                    Response::Failed
+                }
+            }
+            Message::GetLimits(account_id) => {
+                info!("server: GetLimits");
+                Response::Limits(get_limits(account_id, &ds.limits))
+            }
+            Message::GetCompPoints(account_id) => {
+                info!("server: GetCompPoints");
+                Response::CompPoints(get_comp_points(account_id, &ds.comps))
+            }
+            Message::GetCompHistory(account_id) => {
+                info!("server: GetCompHistory");
+                Response::CompHistory(get_comp_history(account_id, &ds.comps))
+            }
         };
         message_packet.response_tx.send(response).unwrap();
     }
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::types::*;
+use crate::utils::get_hand_bet;
+use crate::wallet::{get_account, Account};
+
+// Comp points earned for every chip the house expects to win from a player.
+pub const COMP_POINTS_PER_CHIP: u32 = 100;
+// Points are accrued in thousandths of a point so that small bets still add up over the rounds.
+pub const MILLI_POINTS_PER_POINT: u64 = 1000;
+
+// What a player put into a round, and the comp points it earned them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Comp {
+    pub hands: u32,
+    pub wagered: u32,
+    pub milli_points: u64,
+}
+
+impl Comp {
+    pub fn average_bet(&self) -> u32 {
+        self.wagered.checked_div(self.hands).unwrap_or(0)
+    }
+
+    pub fn points(&self) -> u32 {
+        milli_points_to_points(self.milli_points)
+    }
+}
+
+// Whole points are only for showing to the player, rounded to the nearest point.
+pub fn milli_points_to_points(milli_points: u64) -> u32 {
+    ((milli_points + MILLI_POINTS_PER_POINT / 2) / MILLI_POINTS_PER_POINT) as u32
+}
+
+// Comp points accrued by an account over a round, (account, round, comp).
+pub type CompAccrual = (Uuid, Uuid, Comp);
+
+// The theoretical loss on what was wagered, average bet x hands played x house edge, in comp
+// milli-points.
+pub fn theoretical_loss_milli_points(wagered: u32, (numerator, denominator): (u32, u32)) -> u64 {
+    wagered as u64 * COMP_POINTS_PER_CHIP as u64 * MILLI_POINTS_PER_POINT * numerator as u64
+        / denominator as u64
+}
+
+// Accrue the comps earned by each account that played in a round.  Every settled hand counts as
+// a hand played, hands that nothing was staked on don't earn anything.
+pub fn accrue_comps(
+    round: &Round,
+    outcomes: &[HandOutcome],
+    accounts: &HashMap<Uuid, Account>,
+    rules: &HashMap<Uuid, TableRules>,
+) -> Vec<CompAccrual> {
+    let table_rules = rules.get(&round.game_id).cloned().unwrap_or_default();
+
+    let mut comps: Vec<(Uuid, Comp)> = Vec::new();
+    for (hand_id, _, _) in outcomes.iter().filter(|o| o.1 == round.id) {
+        let Some(account_id) = get_account(*hand_id, &round.hands, accounts) else {
+            continue;
+        };
+        let Some(stake) = get_hand_bet(*hand_id, &round.bets) else {
+            continue;
+        };
+        let idx = match comps.iter().position(|c| c.0 == account_id) {
+            Some(idx) => idx,
+            None => {
+                comps.push((account_id, Comp::default()));
+                comps.len() - 1
+            }
+        };
+        comps[idx].1.hands += 1;
+        comps[idx].1.wagered += stake;
+    }
+
+    comps
+        .into_iter()
+        .map(|(account_id, mut comp)| {
+            comp.milli_points = theoretical_loss_milli_points(comp.wagered, table_rules.house_edge);
+            (account_id, round.id, comp)
+        })
+        .collect()
+}
+
+// The points are totalled up before they're rounded, so nothing is lost round by round.
+pub fn get_comp_points(account_id: Uuid, comps: &[CompAccrual]) -> u32 {
+    let milli_points = comps
+        .iter()
+        .filter(|c| c.0 == account_id)
+        .map(|c| c.2.milli_points)
+        .sum();
+    milli_points_to_points(milli_points)
+}
+
+pub fn get_comp_history(account_id: Uuid, comps: &[CompAccrual]) -> Vec<(Uuid, Comp)> {
+    comps
+        .iter()
+        .filter(|c| c.0 == account_id)
+        .map(|c| (c.1, c.2))
+        .collect()
+}
@@ -1,12 +1,26 @@
+use chrono::{DateTime, Duration, Utc};
 use log::{trace, warn};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::comps::*;
+use crate::ledger::*;
+use crate::limits::*;
+use crate::settlement::*;
+use crate::shoe::Shoe;
+use crate::shuffler::{CryptoShuffler, Shuffler};
+use crate::side_bets::*;
 use crate::types::*;
 use crate::utils::*;
+use crate::wallet::*;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameState {
     Waiting,
+    // The seated players are placing their bets for the next round.
+    Betting,
+    // The dealer is showing an ace and players are deciding whether to take insurance.
+    Insurance,
     Active,
     Finished
 }
@@ -14,34 +28,71 @@ pub enum GameState {
 #[derive(Default)]
 pub struct DataSource {
     pub hands: Vec<Hand>,
-    pub decks: HashMap<Uuid, Deck>, // map of game_id to Deck for a given game
+    pub shoes: HashMap<Uuid, Shoe>, // map of game_id to the Shoe dealt from for a given game
     game_states: HashMap<Uuid, GameState>,
+    pub rules: HashMap<Uuid, TableRules>, // map of game_id to the rules the game is played under
+    first_cards: HashMap<Uuid, usize>, // map of game_id to the index of the first card dealt in the current round
+    pub current_rounds: HashMap<Uuid, Uuid>, // map of game_id to the round_id of the latest round
+    pub rounds: Vec<Round>,            // every finished round, across all of the games
     pub allocations: Vec<CardAllocation>,
     pub hand_states: Vec<HandState>,
     pub actions: Vec<HandAction>,
     pub outcomes: Vec<HandOutcome>,
+    pub settlements: Vec<HandSettlement>,
+    pub insurance_settlements: Vec<HandSettlement>,
+    pub bets: Vec<HandBet>,
+    pub side_bets: Vec<HandSideBet>,
+    pub side_bet_outcomes: Vec<SideBetOutcome>,
+    pub insurance: Vec<HandInsurance>,
+    pub insurance_outcomes: Vec<HandInsuranceOutcome>,
+    pub switches: Vec<HandSwitch>,
+    pub accounts: HashMap<Uuid, Account>, // map of player_id to the player's chips
+    pub transactions: Vec<AccountTransaction>,
+    pub ledger: Vec<LedgerEntry>,
+    pub limits: HashMap<Uuid, PlayerLimits>, // map of player_id to their responsible gaming limits
+    pending_limits: Vec<PendingLimit>,
+    activity: Vec<AccountActivity>,
+    sessions: HashMap<Uuid, DateTime<Utc>>, // map of player_id to when their session started
+    pub comps: Vec<CompAccrual>,
+    betting_deadlines: HashMap<Uuid, DateTime<Utc>>, // map of game_id to when betting closes
+    insurance_deadlines: HashMap<Uuid, DateTime<Utc>>, // map of game_id to when insurance closes
+    betting_rounds: HashMap<Uuid, Uuid>, // map of game_id to the round_id of the round being bet on
     sequence: Vec<Sequence>,
     pub active_hands: Vec<Uuid>,
 }
 
 impl DataSource {
     pub fn add_game(&mut self) -> Uuid {
+        self.add_game_with_rules(TableRules::default())
+    }
+
+    pub fn add_game_with_rules(&mut self, rules: TableRules) -> Uuid {
+        self.add_game_with_shuffler(rules, Box::new(CryptoShuffler))
+    }
+
+    // Create a game whose shoe is shuffled by a specific shuffler, ie a SeededShuffler so that
+    // the game can be replayed.
+    pub fn add_game_with_shuffler(
+        &mut self,
+        rules: TableRules,
+        shuffler: Box<dyn Shuffler>,
+    ) -> Uuid {
         let dealer_id = Uuid::new_v4();
-        self.decks.insert(dealer_id, new_deck());
+        self.shoes.insert(dealer_id, new_shoe(&rules, shuffler));
         self.game_states.insert(dealer_id, GameState::Waiting);
+        self.rules.insert(dealer_id, rules);
         self.hands.push(Hand {
             id: dealer_id,
             player: dealer_id,
             dealer: dealer_id,
+            parent: None,
         });
         dealer_id
     }
 
     //@todo: This needs to return something I guess to indicate success or failure.
     pub fn set_deck(&mut self, game_id: Uuid, deck: Deck) {
-        self.decks
-            .entry(game_id)
-            .and_modify(|current| *current = deck);
+        self.shoes.entry(game_id).and_modify(|shoe| shoe.load(deck));
     }
 
     //@todo: this is a little awkward.  We should potentially have a second function to
@@ -49,52 +100,667 @@ impl DataSource {
     // an action?  Yes this for sure.  id & player should be different id's
     pub fn add_player(&mut self, dealer_id: Uuid) -> Uuid {
         let player_id = Uuid::new_v4();
+        self.add_seat(dealer_id, player_id, player_id)
+    }
+
+    // Seat an account holder at the table, returning the id of their (first) hand.  Sitting down
+    // starts the player's session for the day if they haven't got one going already.
+    pub fn join_table(&mut self, dealer_id: Uuid, account_id: Uuid) -> Result<Uuid, JoinError> {
+        if !self.accounts.contains_key(&account_id) {
+            warn!("Unable to seat {}, no such account", account_id);
+            return Err(JoinError::MissingAccount);
+        }
+        if !self.shoes.contains_key(&dealer_id) {
+            warn!("Unable to seat {}, no such table {}", account_id, dealer_id);
+            return Err(JoinError::MissingTable);
+        }
+
+        let now = Utc::now();
+        let session_start = self
+            .sessions
+            .get(&account_id)
+            .cloned()
+            .filter(|start| now - *start < Duration::hours(LIMIT_PERIOD_HOURS));
+        let limits = get_limits(account_id, &self.limits);
+        if let Err(e) = check_join(&limits, session_start, now) {
+            warn!("Unable to seat {} at {}: {:?}", account_id, dealer_id, e);
+            return Err(JoinError::Limit(e));
+        }
+        self.sessions
+            .insert(account_id, session_start.unwrap_or(now));
+
+        Ok(self.add_seat(dealer_id, Uuid::new_v4(), account_id))
+    }
+
+    fn add_seat(&mut self, dealer_id: Uuid, hand_id: Uuid, player_id: Uuid) -> Uuid {
+        // The first player to sit down at an idle table opens the betting.
+        if matches!(self.game_states.get(&dealer_id), Some(GameState::Waiting)) {
+            self.open_betting(dealer_id);
+        }
+
         self.hands.push(Hand {
-            id: player_id,
+            id: hand_id,
             player: player_id,
             dealer: dealer_id,
+            parent: None,
         });
 
-        player_id
+        // A Blackjack Switch seat plays a second hand alongside the first.
+        let rules = self.rules.get(&dealer_id).cloned().unwrap_or_default();
+        if rules.variant == Variant::Switch {
+            self.hands.push(Hand {
+                id: Uuid::new_v4(),
+                player: player_id,
+                dealer: dealer_id,
+                parent: None,
+            });
+        }
+
+        hand_id
+    }
+
+    pub fn create_account(&mut self, deposit: u32) -> Uuid {
+        let account_id = Uuid::new_v4();
+        self.accounts.insert(account_id, Account::default());
+        // A brand new account doesn't have any limits to hold the deposit back.
+        let _ = self.deposit(account_id, deposit);
+        account_id
+    }
+
+    pub fn deposit(&mut self, account_id: Uuid, amount: u32) -> Result<(), DepositError> {
+        if !self.accounts.contains_key(&account_id) {
+            warn!("Unable to find account {} to deposit into", account_id);
+            return Err(DepositError::MissingAccount);
+        }
+        let limits = get_limits(account_id, &self.limits);
+        if let Err(e) = check_deposit(account_id, amount, &limits, &self.activity, Utc::now()) {
+            warn!(
+                "Rejecting deposit of {} into {}: {:?}",
+                amount, account_id, e
+            );
+            return Err(DepositError::Limit(e));
+        }
+        trace!("server: Depositing {} into {}", amount, account_id);
+        self.record_transactions(vec![(account_id, Transaction::Deposit(amount))]);
+        self.ledger.extend(transfer(
+            None,
+            None,
+            EntryKind::Deposit,
+            LedgerAccount::Cashier,
+            LedgerAccount::Player(account_id),
+            amount,
+        ));
+        Ok(())
+    }
+
+    // Put a limit on an account.  Tightening a limit takes effect straight away, loosening one
+    // has to wait out the delay, returns false if there's no such account.
+    pub fn set_limit(&mut self, account_id: Uuid, limit: Limit) -> bool {
+        if !self.accounts.contains_key(&account_id) {
+            warn!("Unable to find account {} to limit", account_id);
+            return false;
+        }
+
+        // Any change made since replaces a loosening that hasn't come into effect yet.
+        self.pending_limits
+            .retain(|p| p.0 != account_id || !is_same_limit(p.1, limit));
+
+        let limits = self.limits.entry(account_id).or_default();
+        if limits.is_increase(limit) {
+            let effective = Utc::now() + Duration::hours(LIMIT_INCREASE_DELAY_HOURS);
+            trace!(
+                "server: Raising {:?} for {} at {}",
+                limit,
+                account_id,
+                effective
+            );
+            self.pending_limits.push((account_id, limit, effective));
+        } else {
+            trace!("server: Setting {:?} for {}", limit, account_id);
+            limits.apply(limit);
+        }
+        true
+    }
+
+    // The loosened limits on the account that are still waiting to take effect.
+    pub fn get_pending_limits(&self, account_id: Uuid) -> Vec<(Limit, DateTime<Utc>)> {
+        self.pending_limits
+            .iter()
+            .filter(|p| p.0 == account_id)
+            .map(|p| (p.1, p.2))
+            .collect()
+    }
+
+    // Keep the player away from the tables for a while, a cool-off or self-exclusion can be
+    // extended but never cut short.
+    pub fn exclude(&mut self, account_id: Uuid, duration: Duration) -> bool {
+        if !self.accounts.contains_key(&account_id) {
+            warn!("Unable to find account {} to exclude", account_id);
+            return false;
+        }
+        let until = Utc::now() + duration;
+        let limits = self.limits.entry(account_id).or_default();
+        if limits.excluded_until.is_none_or(|current| until > current) {
+            trace!("server: Excluding {} until {}", account_id, until);
+            limits.excluded_until = Some(until);
+        }
+        true
+    }
+
+    // Put the loosened limits into effect once their delay is up.
+    pub fn process_limits(&mut self) {
+        let pending = std::mem::take(&mut self.pending_limits);
+        let (effective, pending) = take_effective_limits(pending, Utc::now());
+        self.pending_limits = pending;
+        for (account_id, limit, _) in effective {
+            trace!("server: Setting {:?} for {}", limit, account_id);
+            self.limits.entry(account_id).or_default().apply(limit);
+        }
+    }
+
+    pub fn get_balance(&self, account_id: Uuid) -> Option<Account> {
+        self.accounts.get(&account_id).cloned()
+    }
+
+    // The chips the player behind the hand has free to bet with.  Seats taken without an account
+    // play with unlimited chips, the same as they play without limits.
+    fn available_funds(&self, hand_id: Uuid) -> u32 {
+        match get_account(hand_id, &self.hands, &self.accounts) {
+            Some(account_id) => self
+                .accounts
+                .get(&account_id)
+                .map_or(0, |account| account.available()),
+            None => u32::MAX,
+        }
+    }
+
+    fn reserve(&mut self, hand_id: Uuid, amount: u32) {
+        if let Some(account_id) = get_account(hand_id, &self.hands, &self.accounts) {
+            self.record_transactions(vec![(account_id, Transaction::Reserved(hand_id, amount))]);
+        }
+    }
+
+    // Book a stake as moving onto the table in the round being bet on or played there.
+    fn post_stake(&mut self, hand_id: Uuid, kind: EntryKind, amount: u32) {
+        let Some(round_id) = self
+            .hands
+            .iter()
+            .find(|h| h.id == hand_id)
+            .and_then(|h| {
+                self.betting_rounds
+                    .get(&h.dealer)
+                    .or(self.current_rounds.get(&h.dealer))
+            })
+            .cloned()
+        else {
+            return;
+        };
+        let entries = stake_entries(hand_id, round_id, kind, amount, &self.hands, &self.accounts);
+        self.ledger.extend(entries);
+    }
+
+    // Check the books, every hand ever dealt is needed to trace the settled hands back to their
+    // accounts.
+    pub fn verify_ledger(&self) -> Result<(), LedgerError> {
+        let hands = self
+            .hands
+            .iter()
+            .chain(self.rounds.iter().flat_map(|r| r.hands.iter()))
+            .cloned()
+            .collect::<Vec<_>>();
+        verify_ledger(
+            &self.ledger,
+            &self.accounts,
+            &hands,
+            &self.outcomes,
+            &self.settlements,
+        )
+    }
+
+    fn record_transactions(&mut self, transactions: Vec<AccountTransaction>) {
+        let now = Utc::now();
+        for (account_id, transaction) in &transactions {
+            if let Some(account) = self.accounts.get_mut(account_id) {
+                account.apply(*transaction);
+            }
+            self.activity.push((*account_id, now, *transaction));
+        }
+        self.transactions.extend(transactions);
+    }
+
+    // Every hand dealt to the seat that the hand belongs to, split hands aside.
+    pub fn get_seat_hands(&self, hand_id: Uuid) -> Vec<Uuid> {
+        let Some(hand) = self.hands.iter().find(|h| h.id == hand_id) else {
+            return Vec::new();
+        };
+        self.hands
+            .iter()
+            .filter(|h| h.player == hand.player && h.dealer == hand.dealer && h.parent.is_none())
+            .map(|h| h.id)
+            .collect()
+    }
+
+    fn open_betting(&mut self, game_id: Uuid) {
+        let rules = self.rules.get(&game_id).cloned().unwrap_or_default();
+        trace!("server: Taking bets for game {}", game_id);
+        self.game_states.insert(game_id, GameState::Betting);
+        self.betting_deadlines
+            .insert(game_id, Utc::now() + rules.betting_time);
+        // The round keeps its id if nobody bet and the table is taking bets for it again.
+        self.betting_rounds
+            .entry(game_id)
+            .or_insert_with(Uuid::new_v4);
+    }
+
+    pub fn place_bet(&mut self, hand_id: Uuid, amount: u32) -> Result<(), BetError> {
+        if let Err(e) = self.validate_bet(hand_id, amount) {
+            warn!("Rejecting bet of {} on {}: {:?}", amount, hand_id, e);
+            return Err(e);
+        }
+
+        trace!("server: Placing bet of {} on {}", amount, hand_id);
+        self.bets.push((hand_id, amount));
+        self.reserve(hand_id, amount);
+        Ok(())
+    }
+
+    // Bets are only taken while the table is betting and have to fall inside of its limits.
+    fn validate_bet(&self, hand_id: Uuid, amount: u32) -> Result<(), BetError> {
+        let hand = self
+            .hands
+            .iter()
+            .find(|h| h.id == hand_id && h.id != h.dealer)
+            .ok_or(BetError::MissingHand)?;
+        if !matches!(self.game_states.get(&hand.dealer), Some(GameState::Betting)) {
+            return Err(BetError::BettingClosed);
+        }
+        if self.bets.iter().any(|b| b.0 == hand_id) {
+            return Err(BetError::AlreadyPlaced);
+        }
+        let rules = self.rules.get(&hand.dealer).cloned().unwrap_or_default();
+        if amount < rules.min_bet {
+            return Err(BetError::BelowTableMinimum);
+        }
+        if amount > rules.max_bet {
+            return Err(BetError::AboveTableMaximum);
+        }
+        if amount > self.available_funds(hand_id) {
+            return Err(BetError::InsufficientFunds);
+        }
+        self.check_wager(hand_id, amount).map_err(BetError::Limit)
+    }
+
+    // Hold a wager up against the limits of the player behind the hand, players without an
+    // account don't have any.
+    fn check_wager(&self, hand_id: Uuid, amount: u32) -> Result<(), LimitError> {
+        let Some(account_id) = get_account(hand_id, &self.hands, &self.accounts) else {
+            return Ok(());
+        };
+        check_wager(
+            account_id,
+            amount,
+            &get_limits(account_id, &self.limits),
+            &self.activity,
+            self.sessions.get(&account_id).cloned(),
+            Utc::now(),
+        )
+    }
+
+    // Deal the round at every table where each seat has bet or the betting time has run out.  A
+    // table that nobody bet at keeps on taking bets.
+    pub fn process_betting(&mut self) {
+        let now = Utc::now();
+        let closed_games = self
+            .game_states
+            .iter()
+            .filter(|(_, state)| matches!(state, GameState::Betting))
+            .map(|(game_id, _)| *game_id)
+            .filter(|game_id| {
+                let all_bet = self
+                    .hands
+                    .iter()
+                    .filter(|h| h.dealer == *game_id && h.id != *game_id)
+                    .all(|h| self.bets.iter().any(|b| b.0 == h.id));
+                let expired = self
+                    .betting_deadlines
+                    .get(game_id)
+                    .is_none_or(|d| now >= *d);
+                all_bet || expired
+            })
+            .collect::<Vec<_>>();
+
+        for game_id in closed_games {
+            let any_bets = self
+                .hands
+                .iter()
+                .any(|h| h.dealer == game_id && self.bets.iter().any(|b| b.0 == h.id));
+            if any_bets {
+                self.start_game(game_id);
+            } else {
+                self.open_betting(game_id);
+            }
+        }
+    }
+
+    // Side bets are settled on the initial deal so they have to be placed while the table is
+    // betting, and only alongside a main bet since a hand without one isn't dealt in.  Returns
+    // false if the bet can't be placed.
+    pub fn place_side_bet(&mut self, hand_id: Uuid, kind: SideBetKind, amount: u32) -> bool {
+        let Some(hand) = self.hands.iter().find(|h| h.id == hand_id) else {
+            warn!("Unable to find hand {} to place a side bet on", hand_id);
+            return false;
+        };
+        let rules = self.rules.get(&hand.dealer).cloned().unwrap_or_default();
+        let is_betting = matches!(self.game_states.get(&hand.dealer), Some(GameState::Betting));
+        let already_placed = self
+            .side_bets
+            .iter()
+            .any(|sb| sb.0 == hand_id && sb.1 == kind);
+        let has_main_bet = self.bets.iter().any(|b| b.0 == hand_id);
+        if !is_betting
+            || !has_main_bet
+            || already_placed
+            || amount == 0
+            || amount > rules.max_bet
+            || amount > self.available_funds(hand_id)
+            || self.check_wager(hand_id, amount).is_err()
+        {
+            warn!("Rejecting {:?} side bet of {} on {}", kind, amount, hand_id);
+            return false;
+        }
+
+        trace!(
+            "server: Placing {:?} side bet of {} on {}",
+            kind,
+            amount,
+            hand_id
+        );
+        self.side_bets.push((hand_id, kind, amount));
+        self.reserve(hand_id, amount);
+        self.post_stake(hand_id, EntryKind::SideBet, amount);
+        true
     }
 
     //@todo: I think this should this return a uuid; reasons 2 fold, we probably
     //       should have a means to identify the action, and we dont want methods
     //       with no return type.
 
-    pub fn add_action(&mut self, hand_id: Uuid, action: Action) {
+    pub fn add_action(
+        &mut self,
+        hand_id: Uuid,
+        action: Action,
+    ) -> Result<(), ActionResolutionError> {
+        if let Err(e) = self.validate_action(hand_id, action) {
+            warn!("Rejecting {:?} for {}: {:?}", action, hand_id, e);
+            return Err(e);
+        }
+
         match action {
             Action::Hit => trace!("server: Adding Hit Action for {}", hand_id),
             Action::Hold => trace!("server: Adding Hold Action for {}", hand_id),
+            Action::Split => trace!("server: Adding Split Action for {}", hand_id),
+            Action::Double => trace!("server: Adding Double Action for {}", hand_id),
+            Action::Surrender => trace!("server: Adding Surrender Action for {}", hand_id),
+            Action::Switch => trace!("server: Adding Switch Action for {}", hand_id),
         };
         self.actions.push((hand_id, action));
+        Ok(())
+    }
+
+    // An action can only be made by the hand whose turn it is, while its game is being played,
+    // and has to be one that the table's rules allow the hand to make.
+    fn validate_action(&self, hand_id: Uuid, action: Action) -> Result<(), ActionResolutionError> {
+        let hand = self
+            .hands
+            .iter()
+            .find(|h| h.id == hand_id)
+            .ok_or(ActionResolutionError::MissingHand)?;
+        if !self.shoes.contains_key(&hand.dealer) {
+            return Err(ActionResolutionError::MissingDeck);
+        }
+        if !matches!(self.game_states.get(&hand.dealer), Some(GameState::Active)) {
+            return Err(ActionResolutionError::GameNotActive);
+        }
+        if !is_hand_active(hand_id, &self.hand_states) {
+            return Err(ActionResolutionError::HandFinished);
+        }
+        // The dealer's hand is played by the table, never by a client.
+        if hand.id == hand.dealer || !self.active_hands.contains(&hand_id) {
+            return Err(ActionResolutionError::NotYourTurn);
+        }
+        if self.actions.iter().any(|a| a.0 == hand_id) {
+            return Err(ActionResolutionError::ActionPending);
+        }
+        let rules = self.rules.get(&hand.dealer).cloned().unwrap_or_default();
+        match action {
+            Action::Hold => {}
+            Action::Hit => {
+                if is_one_card_split_aces(hand, &self.hands, &self.allocations, &self.shoes, &rules)
+                {
+                    return Err(ActionResolutionError::CannotHit);
+                }
+            }
+            Action::Split => {
+                if !can_split(
+                    hand,
+                    &self.hands,
+                    &self.allocations,
+                    &self.shoes,
+                    &self.hand_states,
+                    &rules,
+                ) {
+                    return Err(ActionResolutionError::CannotSplit);
+                }
+            }
+            Action::Double => {
+                if !can_double(
+                    hand,
+                    &self.hands,
+                    &self.allocations,
+                    &self.shoes,
+                    &self.hand_states,
+                    &rules,
+                ) {
+                    return Err(ActionResolutionError::CannotDouble);
+                }
+            }
+            Action::Switch => {
+                if !can_switch(
+                    hand,
+                    &self.hands,
+                    &self.allocations,
+                    &self.hand_states,
+                    &self.switches,
+                    &rules,
+                ) {
+                    return Err(ActionResolutionError::CannotSwitch);
+                }
+            }
+            Action::Surrender => {
+                if !can_surrender(
+                    hand,
+                    &self.hands,
+                    &self.allocations,
+                    &self.hand_states,
+                    &rules,
+                ) {
+                    return Err(ActionResolutionError::CannotSurrender);
+                }
+            }
+        }
+        // Doubling and splitting both put up another stake matching the original bet.
+        let original_stake = self.bets.iter().find(|b| b.0 == hand_id).map_or(0, |b| b.1);
+        if matches!(action, Action::Double | Action::Split) {
+            if original_stake > self.available_funds(hand_id) {
+                return Err(ActionResolutionError::InsufficientFunds);
+            }
+            self.check_wager(hand_id, original_stake)
+                .map_err(ActionResolutionError::Limit)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_game_state(&self, game_id: Uuid) -> Option<GameState> {
+        self.game_states.get(&game_id).cloned()
+    }
+
+    // Record a hands decision during the insurance phase, returns false if the decision isn't
+    // valid for the hand.
+    pub fn add_insurance(&mut self, hand_id: Uuid, decision: Insurance) -> bool {
+        let Some(hand) = self.hands.iter().find(|h| h.id == hand_id) else {
+            warn!("Unable to find hand {} to insure", hand_id);
+            return false;
+        };
+
+        let in_insurance_phase = matches!(
+            self.game_states.get(&hand.dealer),
+            Some(GameState::Insurance)
+        );
+        let already_decided = self.insurance.iter().any(|i| i.0 == hand_id);
+        let in_round = self.sequence.iter().any(|s| s.hand_id == hand_id);
+        let valid = match decision {
+            Insurance::Declined => true,
+            // Insurance is capped at half of the original stake.
+            Insurance::Wager(stake) => {
+                stake > 0
+                    && stake <= self.available_funds(hand_id)
+                    && get_hand_bet(hand_id, &self.bets).is_none_or(|bet| stake <= bet / 2)
+                    && self.check_wager(hand_id, stake).is_ok()
+            }
+            Insurance::EvenMoney => self
+                .hand_states
+                .iter()
+                .any(|hs| hs.0 == hand_id && matches!(hs.2, State::Natural)),
+        };
+
+        if !in_insurance_phase || already_decided || !in_round || hand.id == hand.dealer || !valid {
+            warn!(
+                "Rejecting insurance decision {:?} for {}",
+                decision, hand_id
+            );
+            return false;
+        }
+
+        trace!(
+            "server: Adding insurance decision {:?} for {}",
+            decision,
+            hand_id
+        );
+        self.insurance.push((hand_id, decision));
+        if let Insurance::Wager(stake) = decision {
+            self.reserve(hand_id, stake);
+            self.post_stake(hand_id, EntryKind::Insurance, stake);
+        }
+        true
     }
 
     pub fn start_game(&mut self, game_id: Uuid) {
+        if !matches!(self.game_states.get(&game_id), Some(GameState::Betting)) {
+            warn!("Unable to start game {}, it isn't taking bets", game_id);
+            return;
+        }
+        self.betting_deadlines.remove(&game_id);
+
+        let round_id = self
+            .betting_rounds
+            .remove(&game_id)
+            .unwrap_or_else(Uuid::new_v4);
+        trace!("server: Starting round {} of game {}", round_id, game_id);
+        self.current_rounds.insert(game_id, round_id);
+
+        // Shuffle up between rounds once the cut card has come out.
+        if let Some(shoe) = self.shoes.get_mut(&game_id) {
+            if shoe.is_cut_card_reached() {
+                shoe.reshuffle();
+            }
+        }
+
+        // Record where in the shoe the round starts so that it can be dealt again.
+        if let Some(shoe) = self.shoes.get(&game_id) {
+            self.first_cards.insert(game_id, shoe.next_card());
+        }
+
         // Every hand gets 2 card
-        let allocations = allocate_cards(&self.hands, &self.allocations, game_id, 2);
+        let dealing = self
+            .rules
+            .get(&game_id)
+            .cloned()
+            .unwrap_or_default()
+            .dealing;
+        // Hands without a bet sit the round out.
+        let round_hands = self
+            .hands
+            .iter()
+            .filter(|h| h.id == game_id || self.bets.iter().any(|b| b.0 == h.id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let allocations = allocate_cards(&round_hands, &mut self.shoes, game_id, 2, dealing);
+
+        // The bets go onto the table now that the round is being dealt.
+        let stakes = self
+            .bets
+            .iter()
+            .filter(|b| round_hands.iter().any(|h| h.id == b.0))
+            .cloned()
+            .collect::<Vec<_>>();
+        for (hand_id, stake) in stakes {
+            self.post_stake(hand_id, EntryKind::BetPlaced, stake);
+        }
 
         // Grab the list of the hands that have been updated (this should be all the hands in
         // this game)
-        let updated_hands = allocations
+        let updated_hands = self
+            .hands
             .iter()
-            .filter_map(|ca| self.hands.iter().find(|&h| h.id == ca.hand))
+            .filter(|h| allocations.iter().any(|ca| ca.hand == h.id))
             .cloned()
             .collect::<Vec<_>>();
 
         // Combine the allocations into the master allocation list
         self.allocations.extend(allocations);
 
+        // Side bets only care about the initial deal, so they're settled straight away.
+        let side_bet_outcomes = settle_side_bets(
+            game_id,
+            round_id,
+            &self.hands,
+            &self.side_bets,
+            &self.allocations,
+            &self.shoes,
+        );
+        let transactions = settle_side_bet_outcomes(
+            &side_bet_outcomes,
+            &self.hands,
+            &self.side_bets,
+            &self.accounts,
+        );
+        self.record_transactions(transactions);
+        let settlements = settle_side_bet_wagers(&side_bet_outcomes, &self.side_bets);
+        let entries = settlement_entries(
+            &settlements,
+            EntryKind::SideBet,
+            &self.hands,
+            &self.accounts,
+        );
+        self.ledger.extend(entries);
+        self.side_bet_outcomes.extend(side_bet_outcomes);
+
+        // The dealer's hand isn't looked at until they've had a chance to peek at the hole card.
+        let updated_hands = updated_hands
+            .into_iter()
+            .filter(|h| h.id != game_id)
+            .collect::<Vec<_>>();
+
         // We now need to check the hand states incase anything interesting has
         // resolved from that.
-        let resulting_states = process_hand_states(&updated_hands, &self.allocations, &self.decks);
+        let resulting_states =
+            process_hand_states(&updated_hands, &self.allocations, &self.shoes, &self.rules);
 
         // Merge any hand_states into the master state list
         self.hand_states.extend(resulting_states);
 
         // Determine turn order, currently just extracts all of the hands associated with a dealer.
-        let mut sequence = self
-            .hands
+        let mut sequence = round_hands
             .iter()
             .filter(|h| h.dealer == game_id)
             .map(|h| Sequence {
@@ -116,21 +782,178 @@ impl DataSource {
             }
         });
 
-        // And push the first starting hand
-        match sequence.first() {
+        // Finally push teh sequence onto the master list.
+        self.sequence.extend(sequence);
+
+        // With an ace showing we hold off on peeking at the hole card until the players have had
+        // the chance to insure against a dealer blackjack.
+        // Without a hole card there is nothing to insure against or peek at until the dealer's
+        // turn.
+        let up_card = get_dealer_up_card(game_id, &self.allocations, &self.shoes).cloned();
+        match up_card {
+            _ if dealing != Dealing::HoleCard => {
+                self.game_states.insert(game_id, GameState::Active);
+                self.push_first_hand(game_id);
+            }
+            Some(Card {
+                value: CardValue::Ace,
+                ..
+            }) => {
+                trace!(
+                    "server: Dealer showing an ace, offering insurance for {}",
+                    game_id
+                );
+                let rules = self.rules.get(&game_id).cloned().unwrap_or_default();
+                self.game_states.insert(game_id, GameState::Insurance);
+                self.insurance_deadlines
+                    .insert(game_id, Utc::now() + rules.insurance_time);
+            }
+            Some(card) if should_dealer_peek(&card) => {
+                self.game_states.insert(game_id, GameState::Active);
+                if self.peek_dealer_hand(game_id) {
+                    self.update_outcomes();
+                } else {
+                    self.push_first_hand(game_id);
+                }
+            }
+            _ => {
+                self.game_states.insert(game_id, GameState::Active);
+                self.push_first_hand(game_id);
+            }
+        }
+    }
+
+    // And push the first starting hand that still has something to play for.
+    fn push_first_hand(&mut self, game_id: Uuid) {
+        match self
+            .sequence
+            .iter()
+            .find(|s| s.game_id == game_id && is_hand_active(s.hand_id, &self.hand_states))
+        {
             Some(s) => {
                 //assert!(!is_dealer(get_hand(s.hand_id)));
                 self.active_hands.push(s.hand_id)
             }
             _ => warn!("This should be an error, the sequence vec is empty"),
         };
+    }
 
-        // Finally push teh sequence onto the master list.
-        self.sequence.extend(sequence);
+    // The dealer checks their hole card for blackjack, if they have one it is turned over and the
+    // round is over.  Returns whether the dealer had blackjack.
+    fn peek_dealer_hand(&mut self, game_id: Uuid) -> bool {
+        let dealer = self
+            .hands
+            .iter()
+            .find(|h| h.id == game_id)
+            .cloned()
+            .expect("Unable to find dealer's hand");
+        let dealer_states =
+            process_hand_states(&[dealer], &self.allocations, &self.shoes, &self.rules);
+        let dealer_blackjack = dealer_states
+            .iter()
+            .any(|hs| matches!(hs.2, State::Natural));
+        if !dealer_blackjack {
+            return false;
+        }
+
+        trace!("server: Dealer {} has blackjack", game_id);
+        self.hand_states.extend(dealer_states);
+        self.reveal_hole_card(game_id);
+
+        // Nobody gets to play against a dealer blackjack, every hand stands as dealt.
+        let standing_hands = self
+            .sequence
+            .iter()
+            .filter(|s| s.game_id == game_id && is_hand_active(s.hand_id, &self.hand_states))
+            .map(|s| {
+                let value = get_hand_value(s.hand_id, &self.hands, &self.allocations, &self.shoes);
+                (s.hand_id, s.game_id, State::Holding(value))
+            })
+            .collect::<Vec<_>>();
+        self.hand_states.extend(standing_hands);
+        true
+    }
+
+    fn reveal_hole_card(&mut self, game_id: Uuid) {
+        self.allocations
+            .iter_mut()
+            .filter(|a| a.hand == game_id)
+            .for_each(|a| a.face_up = true);
+    }
+
+    // Close the insurance phase of every game where all the players have made their decision or
+    // the time to decide has run out, anyone still undecided declines.  The dealer then peeks at
+    // their hand and all of the insurance is settled, if the dealer has blackjack the round is
+    // over, otherwise play continues as normal.
+    pub fn process_insurance(&mut self) {
+        let now = Utc::now();
+        let completed_games = self
+            .game_states
+            .iter()
+            .filter(|(_, state)| matches!(state, GameState::Insurance))
+            .map(|(game_id, _)| *game_id)
+            .filter(|game_id| {
+                let expired = self
+                    .insurance_deadlines
+                    .get(game_id)
+                    .is_none_or(|d| now >= *d);
+                expired || is_insurance_complete(*game_id, &self.sequence, &self.insurance)
+            })
+            .collect::<Vec<_>>();
+
+        for game_id in completed_games {
+            self.insurance_deadlines.remove(&game_id);
+            for hand_id in get_undecided_hands(game_id, &self.sequence, &self.insurance) {
+                trace!(
+                    "server: {} didn't decide on insurance, declining it",
+                    hand_id
+                );
+                self.insurance.push((hand_id, Insurance::Declined));
+            }
+            let dealer_blackjack = self.peek_dealer_hand(game_id);
+
+            let rules = self.rules.get(&game_id).cloned().unwrap_or_default();
+            let insurance_outcomes = settle_insurance(
+                game_id,
+                &self.hands,
+                &self.insurance,
+                dealer_blackjack,
+                &rules,
+            );
+
+            // Even money is paid out as a win on the spot.
+            let round_id = self.current_rounds[&game_id];
+            let even_money = insurance_outcomes
+                .iter()
+                .filter(|o| matches!(o.1, InsuranceOutcome::EvenMoney))
+                .map(|o| (o.0, round_id, Outcome::Won(21)))
+                .collect::<Vec<_>>();
+            self.record_outcomes(even_money);
+            let settlements =
+                settle_insurance_wagers(&insurance_outcomes, round_id, &self.insurance);
+            let transactions = settlement_transactions(&settlements, &self.hands, &self.accounts);
+            self.record_transactions(transactions);
+            let entries = settlement_entries(
+                &settlements,
+                EntryKind::Insurance,
+                &self.hands,
+                &self.accounts,
+            );
+            self.ledger.extend(entries);
+            self.insurance_settlements.extend(settlements);
+            self.insurance_outcomes.extend(insurance_outcomes);
+
+            self.game_states.insert(game_id, GameState::Active);
+            if dealer_blackjack {
+                self.update_outcomes();
+            } else {
+                self.push_first_hand(game_id);
+            }
+        }
     }
 
     pub fn process_hit_actions(&mut self) {
-        let allocations = process_hit_actions(&self.actions, &self.hands, &self.allocations);
+        let allocations = process_hit_actions(&self.actions, &self.hands, &mut self.shoes);
 
         // Check for updates to the hand states.
         let updated_hands = allocations
@@ -142,25 +965,208 @@ impl DataSource {
         // Merge allocations into the master list.
         self.allocations.extend(allocations);
 
-        // Check if any of the new hands have busted or hit blackjack.
-        let resulting_states = process_hand_states(&updated_hands, &self.allocations, &self.decks);
-        //todo!("need to add a step here to iterate hand states to check for children that need to be added");
+        // Check if any of the new hands have busted or reached 21.
+        let resulting_states =
+            process_hand_states(&updated_hands, &self.allocations, &self.shoes, &self.rules);
 
         // Merge into the master state list
         self.hand_states.extend(resulting_states);
     }
 
+    pub fn get_current_round(&self, game_id: Uuid) -> Option<Uuid> {
+        self.current_rounds.get(&game_id).cloned()
+    }
+
+    pub fn process_switch_actions(&mut self) {
+        let switches = process_switch_actions(&self.actions, &self.hands);
+
+        for (hand_id, partner_id) in &switches {
+            // Swap the second card dealt to each of the hands.
+            let second_card = |allocations: &[CardAllocation], id: Uuid| {
+                allocations.iter().rposition(|a| a.hand == id)
+            };
+            if let (Some(first), Some(second)) = (
+                second_card(&self.allocations, *hand_id),
+                second_card(&self.allocations, *partner_id),
+            ) {
+                self.allocations[first].hand = *partner_id;
+                self.allocations[second].hand = *hand_id;
+            }
+
+            // A 21 made by switching isn't a natural.
+            let switched_hands = self
+                .hands
+                .iter()
+                .filter(|h| h.id == *hand_id || h.id == *partner_id)
+                .cloned()
+                .collect::<Vec<_>>();
+            let resulting_states =
+                process_hand_states(&switched_hands, &self.allocations, &self.shoes, &self.rules)
+                    .into_iter()
+                    .map(|(id, dealer, state)| match state {
+                        State::Natural => (id, dealer, State::Holding(21)),
+                        _ => (id, dealer, state),
+                    });
+            self.hand_states.extend(resulting_states);
+        }
+
+        self.switches.extend(switches);
+    }
+
+    pub fn process_split_actions(&mut self) {
+        let split_hands = process_split_actions(&self.actions, &self.hands);
+
+        for split_hand in split_hands {
+            let parent_id = split_hand.parent.expect("Split hand has no parent");
+
+            // Move the second card of the pair across to the new hand.
+            if let Some(allocation) = self
+                .allocations
+                .iter_mut()
+                .rev()
+                .find(|a| a.hand == parent_id)
+            {
+                allocation.hand = split_hand.id;
+            }
+
+            // The new hand takes its turn straight after the hand it was split from.
+            if let Some(idx) = self.sequence.iter().position(|s| s.hand_id == parent_id) {
+                self.sequence.insert(
+                    idx + 1,
+                    Sequence {
+                        game_id: split_hand.dealer,
+                        hand_id: split_hand.id,
+                    },
+                );
+            }
+
+            let parent = self
+                .hands
+                .iter()
+                .find(|h| h.id == parent_id)
+                .cloned()
+                .expect("Unable to find Hand");
+            self.hands.push(split_hand.clone());
+
+            // and carries a stake matching the original bet.
+            if let Some(stake) = get_hand_bet(parent_id, &self.bets) {
+                self.bets.push((split_hand.id, stake));
+                self.reserve(split_hand.id, stake);
+                self.post_stake(split_hand.id, EntryKind::BetPlaced, stake);
+            }
+
+            // Both halves of the pair are now dealt their second card.
+            let updated_hands = vec![parent, split_hand];
+            for hand in &updated_hands {
+                let allocation = deal_card(hand, &mut self.shoes);
+                self.allocations.push(allocation);
+            }
+
+            let resulting_states = process_split_states(
+                &updated_hands,
+                &self.hands,
+                &self.allocations,
+                &self.shoes,
+                &self.rules,
+            );
+            self.hand_states.extend(resulting_states);
+        }
+    }
+
+    pub fn process_double_actions(&mut self) {
+        let allocations = process_double_actions(&self.actions, &self.hands, &mut self.shoes);
+
+        let doubled_hands = allocations
+            .iter()
+            .filter_map(|ca| self.hands.iter().find(|&h| h.id == ca.hand))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Doubling matches the original stake on the hand, as does every Pontoon buy.
+        for hand in &doubled_hands {
+            if let Some(stake) = self.bets.iter().find(|b| b.0 == hand.id).map(|b| b.1) {
+                self.bets.push((hand.id, stake));
+                self.reserve(hand.id, stake);
+                self.post_stake(hand.id, EntryKind::BetPlaced, stake);
+            }
+        }
+
+        // Merge allocations into the master list.
+        self.allocations.extend(allocations);
+
+        // Doubled hands are done, so they go straight to holding or bust.  A hand that bought a
+        // card in Pontoon plays on.
+        let (bought_hands, doubled_hands): (Vec<_>, Vec<_>) =
+            doubled_hands.into_iter().partition(|h| {
+                self.rules
+                    .get(&h.dealer)
+                    .is_some_and(|r| r.variant == Variant::Pontoon)
+            });
+        let resulting_states =
+            process_standing_states(&doubled_hands, &self.allocations, &self.shoes);
+        self.hand_states.extend(resulting_states);
+        let resulting_states =
+            process_hand_states(&bought_hands, &self.allocations, &self.shoes, &self.rules);
+        self.hand_states.extend(resulting_states);
+    }
+
+    pub fn process_surrender_actions(&mut self) {
+        let surrender_states =
+            process_surrender_actions(&self.actions, &self.hands, &self.allocations, &self.shoes);
+
+        // Merge these into the master state list
+        self.hand_states.extend(surrender_states);
+    }
+
     pub fn process_hold_actions(&mut self) {
         let hold_states =
-            process_hold_actions(&self.hands, &self.actions, &self.allocations, &self.decks);
+            process_hold_actions(&self.hands, &self.actions, &self.allocations, &self.shoes);
 
         // Merge these into the master state list
         self.hand_states.extend(hold_states);
     }
 
+    pub fn is_dealers_turn(&self) -> bool {
+        self.hands
+            .iter()
+            .any(|h| h.id == h.dealer && self.active_hands.contains(&h.id))
+    }
+
+    pub fn process_dealer_hands(&mut self) {
+        // It's the dealer's turn so the hole card is turned over before they play.
+        let dealers = self
+            .active_hands
+            .iter()
+            .filter(|id| self.hands.iter().any(|h| h.id == **id && h.id == h.dealer))
+            .cloned()
+            .collect::<Vec<_>>();
+        for dealer_id in dealers {
+            self.reveal_hole_card(dealer_id);
+        }
+
+        let allocations = play_dealer_hands(
+            &self.active_hands,
+            &self.hands,
+            &self.allocations,
+            &mut self.shoes,
+            &self.hand_states,
+            &self.rules,
+        );
+        self.allocations.extend(allocations);
+
+        // The dealer is done as soon as they've drawn
+        let dealer_hands = self
+            .hands
+            .iter()
+            .filter(|h| h.id == h.dealer && self.active_hands.contains(&h.id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let dealer_states = process_standing_states(&dealer_hands, &self.allocations, &self.shoes);
+        self.hand_states.extend(dealer_states);
+    }
+
     pub fn resolve_turn(&mut self) {
-        let new_outcomes = resolve_outcomes(&self.hand_states, &self.outcomes);
-        self.outcomes.extend(new_outcomes);
+        self.update_outcomes();
 
         self.active_hands = self
             .active_hands
@@ -177,4 +1183,174 @@ impl DataSource {
 
         self.actions.clear();
     }
+
+    fn update_outcomes(&mut self) {
+        // The doubled stakes at OBO tables are returned to the players still standing when the
+        // dealer turns out to have a natural, a hand that went bust has already lost them.
+        let obo_naturals = self
+            .hand_states
+            .iter()
+            .filter(|hs| hs.0 == hs.1 && matches!(hs.2, State::Natural))
+            .filter(|hs| {
+                matches!(
+                    self.rules.get(&hs.0).map(|r| r.dealing),
+                    Some(Dealing::NoHoleCard {
+                        original_bets_only: true
+                    })
+                )
+            })
+            .map(|hs| hs.0)
+            .collect::<Vec<_>>();
+        for game_id in obo_naturals {
+            let round_id = self.current_rounds.get(&game_id).cloned();
+            let hand_ids = self
+                .hand_states
+                .iter()
+                .filter(|hs| hs.1 == game_id && hs.0 != game_id && is_standing(&hs.2))
+                .filter(|hs| {
+                    !self
+                        .outcomes
+                        .iter()
+                        .any(|o| o.0 == hs.0 && Some(o.1) == round_id)
+                })
+                .map(|hs| hs.0)
+                .collect::<Vec<_>>();
+            let game_bets = drain_where(&mut self.bets, |b| hand_ids.contains(&b.0));
+            let kept_bets = original_bets_only(&game_bets);
+
+            // and the chips that were reserved for them are freed up again.
+            let mut returned = Vec::new();
+            for hand_id in &hand_ids {
+                let Some(staked) = get_hand_bet(*hand_id, &game_bets) else {
+                    continue;
+                };
+                let kept = get_hand_bet(*hand_id, &kept_bets).unwrap_or(0);
+                let Some(account_id) = get_account(*hand_id, &self.hands, &self.accounts) else {
+                    continue;
+                };
+                if staked > kept {
+                    returned.push((account_id, Transaction::Released(*hand_id, staked - kept)));
+                    // The stake was never in play, so it's taken back off the table rather than
+                    // paid out.
+                    self.ledger.extend(transfer(
+                        round_id,
+                        Some(*hand_id),
+                        EntryKind::BetPlaced,
+                        LedgerAccount::Table(game_id),
+                        LedgerAccount::Player(account_id),
+                        staked - kept,
+                    ));
+                }
+            }
+            self.record_transactions(returned);
+            self.bets.extend(kept_bets);
+        }
+
+        let new_outcomes = resolve_outcomes(
+            &self.hand_states,
+            &self.outcomes,
+            &self.current_rounds,
+            &self.hands,
+            &self.rules,
+        );
+        self.record_outcomes(new_outcomes);
+
+        // Flag any of the games that have played out completely.
+        for (game_id, state) in self.game_states.iter_mut() {
+            if matches!(state, GameState::Active)
+                && is_game_complete(*game_id, &self.sequence, &self.hand_states)
+            {
+                trace!("server: Game {} has finished", game_id);
+                *state = GameState::Finished;
+            }
+        }
+    }
+
+    // Merge new outcomes into the master list, settling the chips staked on each of the hands.
+    fn record_outcomes(&mut self, outcomes: Vec<HandOutcome>) {
+        let settlements = settle_outcomes(&outcomes, &self.hands, &self.bets, &self.rules);
+        let transactions = settlement_transactions(&settlements, &self.hands, &self.accounts);
+        self.record_transactions(transactions);
+        let entries = settlement_entries(
+            &settlements,
+            EntryKind::BetPlaced,
+            &self.hands,
+            &self.accounts,
+        );
+        self.ledger.extend(entries);
+        self.settlements.extend(settlements);
+        self.outcomes.extend(outcomes);
+    }
+
+    // Archive the round at every table that has finished playing one and get the table ready
+    // for the next, the players stay seated but any hands split during the round are gone.
+    pub fn process_finished_games(&mut self) {
+        let finished_games = self
+            .game_states
+            .iter()
+            .filter(|(_, state)| matches!(state, GameState::Finished))
+            .map(|(game_id, _)| *game_id)
+            .collect::<Vec<_>>();
+
+        for game_id in finished_games {
+            let round = self.archive_round(game_id);
+            trace!("server: Archived round {} of game {}", round.id, game_id);
+            let comps = accrue_comps(&round, &self.outcomes, &self.accounts, &self.rules);
+            self.comps.extend(comps);
+            self.rounds.push(round);
+            self.game_states.insert(game_id, GameState::Waiting);
+
+            // Take bets for the next round straight away as long as someone is still sitting at
+            // the table.
+            if self
+                .hands
+                .iter()
+                .any(|h| h.dealer == game_id && h.id != game_id)
+            {
+                self.open_betting(game_id);
+            }
+        }
+    }
+
+    fn archive_round(&mut self, game_id: Uuid) -> Round {
+        let hand_ids = self
+            .hands
+            .iter()
+            .filter(|h| h.dealer == game_id)
+            .map(|h| h.id)
+            .collect::<Vec<_>>();
+
+        let hands = self
+            .hands
+            .iter()
+            .filter(|h| h.dealer == game_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        self.hands
+            .retain(|h| h.dealer != game_id || h.parent.is_none());
+
+        self.active_hands.retain(|id| !hand_ids.contains(id));
+        self.actions.retain(|a| !hand_ids.contains(&a.0));
+
+        Round {
+            id: self.current_rounds[&game_id],
+            game_id,
+            shuffles: self
+                .first_cards
+                .remove(&game_id)
+                .zip(self.shoes.get(&game_id))
+                .map_or(Vec::new(), |(card_idx, shoe)| shoe.shuffles_since(card_idx)),
+            hands,
+            allocations: drain_where(&mut self.allocations, |a| a.dealer == game_id),
+            hand_states: drain_where(&mut self.hand_states, |hs| hs.1 == game_id),
+            sequence: drain_where(&mut self.sequence, |s| s.game_id == game_id),
+            bets: drain_where(&mut self.bets, |b| hand_ids.contains(&b.0)),
+            side_bets: drain_where(&mut self.side_bets, |sb| hand_ids.contains(&sb.0)),
+            insurance: drain_where(&mut self.insurance, |i| hand_ids.contains(&i.0)),
+            insurance_outcomes: drain_where(&mut self.insurance_outcomes, |i| {
+                hand_ids.contains(&i.0)
+            }),
+            switches: drain_where(&mut self.switches, |s| hand_ids.contains(&s.0)),
+        }
+    }
 }
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::settlement::HandSettlement;
+use crate::types::*;
+use crate::wallet::{get_account, Account};
+
+// The books that chips move between.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LedgerAccount {
+    // Where deposited chips come from.
+    Cashier,
+    // A player's chips that aren't staked on anything yet.
+    Player(Uuid),
+    // The chips staked at a table (game) that are waiting to be settled.
+    Table(Uuid),
+    // The table's bank, it pays out the winnings and takes the losing stakes and the rake.
+    House(Uuid),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    Deposit,
+    BetPlaced,
+    WinPaid,
+    // The stake handed back to the player, all of it on a push or half of it on a surrender.
+    PushReturned,
+    StakeLost,
+    // Insurance and side bets are booked under their own kind for every movement.
+    Insurance,
+    SideBet,
+    Rake,
+}
+
+// One side of a chip movement, credits are positive and debits negative.  Entries are always
+// written in pairs that cancel each other out.  The round and hand are the ids the movement was
+// made for, a deposit isn't made for either.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LedgerEntry {
+    pub round: Option<Uuid>,
+    pub hand: Option<Uuid>,
+    pub kind: EntryKind,
+    pub account: LedgerAccount,
+    pub amount: i64,
+}
+
+// Why the ledger failed verification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LedgerError {
+    // The entries made for a (round, hand) don't cancel each other out.
+    Unbalanced(Option<Uuid> /*round*/, Option<Uuid> /*hand*/),
+    // The chips held in the player accounts don't match the chips the ledger says they hold.
+    NotConserved { accounts: i64, ledger: i64 },
+    // A settled outcome that the ledger doesn't pay out in full, or that was never settled.
+    Unmatched(Uuid /*hand*/, Uuid /*round*/),
+}
+
+// Move chips from one account to another as a balanced pair of entries.
+pub fn transfer(
+    round: Option<Uuid>,
+    hand: Option<Uuid>,
+    kind: EntryKind,
+    from: LedgerAccount,
+    to: LedgerAccount,
+    amount: u32,
+) -> Vec<LedgerEntry> {
+    if amount == 0 {
+        return Vec::new();
+    }
+    let entry = |account, amount| LedgerEntry {
+        round,
+        hand,
+        kind,
+        account,
+        amount,
+    };
+    vec![entry(from, -(amount as i64)), entry(to, amount as i64)]
+}
+
+// Move a stake from the player behind the hand onto the table.  Hands that don't belong to an
+// account have nothing to book.
+pub fn stake_entries(
+    hand_id: Uuid,
+    round_id: Uuid,
+    kind: EntryKind,
+    amount: u32,
+    hands: &[Hand],
+    accounts: &HashMap<Uuid, Account>,
+) -> Vec<LedgerEntry> {
+    let Some(hand) = hands.iter().find(|h| h.id == hand_id) else {
+        return Vec::new();
+    };
+    let Some(account_id) = get_account(hand_id, hands, accounts) else {
+        return Vec::new();
+    };
+    transfer(
+        Some(round_id),
+        Some(hand_id),
+        kind,
+        LedgerAccount::Player(account_id),
+        LedgerAccount::Table(hand.dealer),
+        amount,
+    )
+}
+
+// Book the settlement of every wager.  The main bet (BetPlaced) is broken down into the wins
+// paid, the stakes returned and the stakes lost, every other wager is booked under its own kind.
+pub fn settlement_entries(
+    settlements: &[HandSettlement],
+    wager: EntryKind,
+    hands: &[Hand],
+    accounts: &HashMap<Uuid, Account>,
+) -> Vec<LedgerEntry> {
+    let kind = |kind| {
+        if wager == EntryKind::BetPlaced {
+            kind
+        } else {
+            wager
+        }
+    };
+
+    let mut entries = Vec::new();
+    for (hand_id, round_id, settlement) in settlements {
+        let Some(hand) = hands.iter().find(|h| h.id == *hand_id) else {
+            continue;
+        };
+        let Some(account_id) = get_account(*hand_id, hands, accounts) else {
+            continue;
+        };
+        let (round, hand_id) = (Some(*round_id), Some(*hand_id));
+        let player = LedgerAccount::Player(account_id);
+        let table = LedgerAccount::Table(hand.dealer);
+        let house = LedgerAccount::House(hand.dealer);
+
+        if settlement.payout > settlement.stake {
+            // The stake comes back along with the winnings, the rake is then taken out of them.
+            let won = settlement.payout - settlement.stake + settlement.rake;
+            let paid = kind(EntryKind::WinPaid);
+            entries.extend(transfer(
+                round,
+                hand_id,
+                paid,
+                table,
+                player,
+                settlement.stake,
+            ));
+            entries.extend(transfer(round, hand_id, paid, house, player, won));
+            let rake = kind(EntryKind::Rake);
+            entries.extend(transfer(
+                round,
+                hand_id,
+                rake,
+                player,
+                house,
+                settlement.rake,
+            ));
+        } else {
+            let returned = kind(EntryKind::PushReturned);
+            entries.extend(transfer(
+                round,
+                hand_id,
+                returned,
+                table,
+                player,
+                settlement.payout,
+            ));
+            let lost = settlement.stake - settlement.payout;
+            let lost_kind = kind(EntryKind::StakeLost);
+            entries.extend(transfer(round, hand_id, lost_kind, table, house, lost));
+        }
+    }
+    entries
+}
+
+// Prove that the ledger is sound.  Every movement has to balance, the player accounts have to
+// hold exactly the chips the ledger has them holding, free or staked, and every settled outcome
+// has to have been paid out to the player in full.  Hands are every hand that's been dealt,
+// archived rounds included, so that split hands can still be traced back to their account.
+pub fn verify_ledger(
+    ledger: &[LedgerEntry],
+    accounts: &HashMap<Uuid, Account>,
+    hands: &[Hand],
+    outcomes: &[HandOutcome],
+    settlements: &[HandSettlement],
+) -> Result<(), LedgerError> {
+    let mut movements: HashMap<(Option<Uuid>, Option<Uuid>), i64> = HashMap::new();
+    for entry in ledger {
+        *movements.entry((entry.round, entry.hand)).or_default() += entry.amount;
+    }
+    if let Some(((round, hand), _)) = movements.iter().find(|(_, sum)| **sum != 0) {
+        return Err(LedgerError::Unbalanced(*round, *hand));
+    }
+
+    // Staked chips are still the player's until they're settled, so the table is counted too.
+    let held = ledger
+        .iter()
+        .filter(|e| {
+            matches!(
+                e.account,
+                LedgerAccount::Player(_) | LedgerAccount::Table(_)
+            )
+        })
+        .map(|e| e.amount)
+        .sum::<i64>();
+    let balances = accounts.values().map(|a| a.balance as i64).sum::<i64>();
+    if held != balances {
+        return Err(LedgerError::NotConserved {
+            accounts: balances,
+            ledger: held,
+        });
+    }
+
+    for (hand_id, round_id, _) in outcomes {
+        let Some(account_id) = get_account(*hand_id, hands, accounts) else {
+            continue;
+        };
+        let Some(settlement) = settlements
+            .iter()
+            .find(|s| s.0 == *hand_id && s.1 == *round_id)
+            .map(|s| s.2)
+        else {
+            return Err(LedgerError::Unmatched(*hand_id, *round_id));
+        };
+        let paid = ledger
+            .iter()
+            .filter(|e| e.round == Some(*round_id) && e.hand == Some(*hand_id))
+            .filter(|e| e.account == LedgerAccount::Player(account_id))
+            .filter(|e| {
+                matches!(
+                    e.kind,
+                    EntryKind::WinPaid | EntryKind::PushReturned | EntryKind::Rake
+                )
+            })
+            .map(|e| e.amount)
+            .sum::<i64>();
+        if paid != settlement.payout as i64 {
+            return Err(LedgerError::Unmatched(*hand_id, *round_id));
+        }
+    }
+    Ok(())
+}
+
+pub fn get_ledger_entries(round_id: Uuid, ledger: &[LedgerEntry]) -> Vec<LedgerEntry> {
+    ledger
+        .iter()
+        .filter(|e| e.round == Some(round_id))
+        .cloned()
+        .collect()
+}
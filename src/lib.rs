@@ -1,11 +1,33 @@
 mod backend;
+mod comps;
 mod data_source;
+mod ledger;
+mod limits;
+mod settlement;
+mod shoe;
+mod shuffler;
+mod side_bets;
 mod types;
 mod utils;
+mod wallet;
 
 pub use backend::{Message, MessagePacket, Resource, Response};
-pub use data_source::DataSource;
-pub use types::{Action, Card, CardValue, Deck, Hand, Outcome, Suit};
+pub use comps::{get_comp_points, Comp};
+pub use data_source::{DataSource, GameState};
+pub use ledger::{EntryKind, LedgerAccount, LedgerEntry, LedgerError};
+pub use limits::{Limit, LimitError, PlayerLimits, LIMIT_INCREASE_DELAY_HOURS};
+pub use settlement::Settlement;
+pub use shoe::Shoe;
+pub use shuffler::{shuffle_with_seed, CryptoShuffler, SeededShuffler, ShuffleSeed, Shuffler};
+pub use side_bets::{
+    Pair, PerfectPairs, PokerHand, SideBet, SideBetResult, SideBetWin, TwentyOnePlusThree,
+};
+pub use types::{
+    Action, Bonus, Card, CardValue, Dealing, Deck, DoubleRule, Hand, Insurance, InsuranceOutcome,
+    Outcome, SideBetKind, Suit, SuitMatch, Surrender, TableRules, Variant,
+};
+pub use utils::{ActionResolutionError, BetError, DepositError, JoinError};
+pub use wallet::{Account, Transaction};
 
 use std::sync::mpsc;
 use std::thread;
@@ -31,11 +53,20 @@ pub fn start_backend(
     thread::spawn(move || {
         loop {
             // 
-            if !ds.actions.is_empty() {
+            ds.process_limits();
+            ds.process_betting();
+            ds.process_insurance();
+            if !ds.actions.is_empty() || ds.is_dealers_turn() {
+                ds.process_switch_actions();
+                ds.process_split_actions();
                 ds.process_hit_actions();
+                ds.process_double_actions();
+                ds.process_surrender_actions();
                 ds.process_hold_actions();
+                ds.process_dealer_hands();
                 ds.resolve_turn();
             }
+            ds.process_finished_games();
         }
     })
 }
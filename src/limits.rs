@@ -0,0 +1,189 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::mem::discriminant;
+use uuid::Uuid;
+
+use crate::wallet::Transaction;
+
+// Deposit, loss and wager limits are for the day, counted back from now.
+pub const LIMIT_PERIOD_HOURS: i64 = 24;
+// Loosening a limit only takes effect once it's had time to cool off.
+pub const LIMIT_INCREASE_DELAY_HOURS: i64 = 24;
+
+// A responsible gaming limit that a player (or the operator) can put on an account.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    Deposit(u32),
+    // The most the player can lose, after winnings.
+    Loss(u32),
+    // The most the player can stake, every bet, double, split, insurance and side bet counts.
+    Wager(u32),
+    // How long the player can play for, counted from the first time they sat down that day.
+    SessionTime(Duration),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlayerLimits {
+    pub deposit: Option<u32>,
+    pub loss: Option<u32>,
+    pub wager: Option<u32>,
+    pub session_time: Option<Duration>,
+    // Cooling off and self-exclusion both keep the player away from the tables until then.
+    pub excluded_until: Option<DateTime<Utc>>,
+}
+
+impl PlayerLimits {
+    pub fn apply(&mut self, limit: Limit) {
+        match limit {
+            Limit::Deposit(amount) => self.deposit = Some(amount),
+            Limit::Loss(amount) => self.loss = Some(amount),
+            Limit::Wager(amount) => self.wager = Some(amount),
+            Limit::SessionTime(duration) => self.session_time = Some(duration),
+        }
+    }
+
+    // Whether the limit would loosen the one already in place, setting a limit for the first
+    // time only ever tightens things.
+    pub fn is_increase(&self, limit: Limit) -> bool {
+        match limit {
+            Limit::Deposit(amount) => self.deposit.is_some_and(|d| amount > d),
+            Limit::Loss(amount) => self.loss.is_some_and(|l| amount > l),
+            Limit::Wager(amount) => self.wager.is_some_and(|w| amount > w),
+            Limit::SessionTime(duration) => self.session_time.is_some_and(|s| duration > s),
+        }
+    }
+}
+
+// Why a player was stopped by their limits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitError {
+    Excluded(DateTime<Utc> /*until*/),
+    DepositLimit,
+    LossLimit,
+    WagerLimit,
+    SessionTime,
+}
+
+// A loosened limit waiting to take effect on an account, (account, limit, when it takes effect).
+pub type PendingLimit = (Uuid, Limit, DateTime<Utc>);
+
+// A transaction along with when it was made, limits are counted over a period of time.
+pub type AccountActivity = (Uuid /*account*/, DateTime<Utc>, Transaction);
+
+pub fn is_same_limit(a: Limit, b: Limit) -> bool {
+    discriminant(&a) == discriminant(&b)
+}
+
+// Every transaction on the account that falls inside of the current limit period.
+fn period_activity(
+    account_id: Uuid,
+    activity: &[AccountActivity],
+    now: DateTime<Utc>,
+) -> impl Iterator<Item = Transaction> + '_ {
+    let start = now - Duration::hours(LIMIT_PERIOD_HOURS);
+    activity
+        .iter()
+        .filter(move |a| a.0 == account_id && a.1 > start)
+        .map(|a| a.2)
+}
+
+fn check_exclusion(limits: &PlayerLimits, now: DateTime<Utc>) -> Result<(), LimitError> {
+    match limits.excluded_until {
+        Some(until) if until > now => Err(LimitError::Excluded(until)),
+        _ => Ok(()),
+    }
+}
+
+// Whether a session that started at the given time has run over the player's session limit.
+fn check_session(
+    limits: &PlayerLimits,
+    session_start: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), LimitError> {
+    match (limits.session_time, session_start) {
+        (Some(session_time), Some(start)) if now - start >= session_time => {
+            Err(LimitError::SessionTime)
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn check_deposit(
+    account_id: Uuid,
+    amount: u32,
+    limits: &PlayerLimits,
+    activity: &[AccountActivity],
+    now: DateTime<Utc>,
+) -> Result<(), LimitError> {
+    check_exclusion(limits, now)?;
+    if let Some(deposit_limit) = limits.deposit {
+        let deposited = period_activity(account_id, activity, now)
+            .map(|t| match t {
+                Transaction::Deposit(amount) => amount as i64,
+                _ => 0,
+            })
+            .sum::<i64>();
+        if deposited + amount as i64 > deposit_limit as i64 {
+            return Err(LimitError::DepositLimit);
+        }
+    }
+    Ok(())
+}
+
+pub fn check_join(
+    limits: &PlayerLimits,
+    session_start: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), LimitError> {
+    check_exclusion(limits, now)?;
+    check_session(limits, session_start, now)
+}
+
+// A wager is turned away if it would take the player past their wager limit, or if losing it
+// would take them past their loss limit.
+pub fn check_wager(
+    account_id: Uuid,
+    amount: u32,
+    limits: &PlayerLimits,
+    activity: &[AccountActivity],
+    session_start: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), LimitError> {
+    check_exclusion(limits, now)?;
+    check_session(limits, session_start, now)?;
+
+    let (mut wagered, mut net_loss) = (0_i64, 0_i64);
+    for transaction in period_activity(account_id, activity, now) {
+        match transaction {
+            Transaction::Reserved(_, amount) => wagered += amount as i64,
+            Transaction::Lost(_, amount) => net_loss += amount as i64,
+            Transaction::Won(_, amount) => net_loss -= amount as i64,
+            _ => {}
+        }
+    }
+    if limits
+        .wager
+        .is_some_and(|limit| wagered + amount as i64 > limit as i64)
+    {
+        return Err(LimitError::WagerLimit);
+    }
+    if limits
+        .loss
+        .is_some_and(|limit| net_loss + amount as i64 > limit as i64)
+    {
+        return Err(LimitError::LossLimit);
+    }
+    Ok(())
+}
+
+// Split the pending limits into the ones that have come into effect and the ones still waiting.
+pub fn take_effective_limits(
+    pending: Vec<PendingLimit>,
+    now: DateTime<Utc>,
+) -> (Vec<PendingLimit>, Vec<PendingLimit>) {
+    pending.into_iter().partition(|p| p.2 <= now)
+}
+
+pub fn get_limits(account_id: Uuid, limits: &HashMap<Uuid, PlayerLimits>) -> PlayerLimits {
+    limits.get(&account_id).cloned().unwrap_or_default()
+}
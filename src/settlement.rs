@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::side_bets::{SideBetOutcome, SideBetResult};
+use crate::types::*;
+use crate::utils::{bonus_payout, get_hand_bet};
+
+// What a wager paid out.  The payout is everything handed back to the player, the returned
+// stake included, so a push pays back exactly the stake and a loss pays nothing.  Any rake the
+// house took from the winnings has already come out of the payout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settlement {
+    pub stake: u32,
+    pub payout: u32,
+    pub rake: u32,
+}
+
+impl Settlement {
+    // What the player came out ahead (or behind) by.
+    pub fn net(&self) -> i64 {
+        self.payout as i64 - self.stake as i64
+    }
+}
+
+// The settlement of the wager on a hand in a round, the same (hand, round) pair as the
+// HandOutcome it settles.
+pub type HandSettlement = (Uuid /*hand*/, Uuid /*round*/, Settlement);
+
+// Winnings paid at a (numerator, denominator) ratio of the stake.
+pub fn winnings(stake: u32, (numerator, denominator): (u32, u32)) -> u32 {
+    stake * numerator / denominator
+}
+
+// Everything an outcome pays back on the stake.
+fn outcome_payout(stake: u32, outcome: Outcome, rules: &TableRules) -> u32 {
+    match outcome {
+        Outcome::Won(_) => stake + stake,
+        Outcome::Natural => stake + winnings(stake, rules.blackjack_payout),
+        Outcome::Charlie(_) => stake + winnings(stake, rules.charlie_payout),
+        Outcome::Bonus(bonus) => stake + winnings(stake, bonus_payout(bonus)),
+        Outcome::Push(_) => stake,
+        // Half of the stake is given back.
+        Outcome::Surrendered => stake / 2,
+        Outcome::Lost(_) => 0,
+    }
+}
+
+// Settle every one of the new outcomes against the stake on its hand.  A hand that nothing was
+// staked on has nothing to settle.
+pub fn settle_outcomes(
+    outcomes: &[HandOutcome],
+    hands: &[Hand],
+    bets: &[HandBet],
+    rules: &HashMap<Uuid, TableRules>,
+) -> Vec<HandSettlement> {
+    outcomes
+        .iter()
+        .filter_map(|(hand_id, round_id, outcome)| {
+            let stake = get_hand_bet(*hand_id, bets)?;
+            let table_rules = hands
+                .iter()
+                .find(|h| h.id == *hand_id)
+                .and_then(|h| rules.get(&h.dealer))
+                .cloned()
+                .unwrap_or_default();
+            let payout = outcome_payout(stake, *outcome, &table_rules);
+            let rake = winnings(payout.saturating_sub(stake), table_rules.rake);
+            Some((
+                *hand_id,
+                *round_id,
+                Settlement {
+                    stake,
+                    payout: payout - rake,
+                    rake,
+                },
+            ))
+        })
+        .collect()
+}
+
+// Settle the insurance wagers, a winning wager is paid at the table's insurance payout.  Even
+// money is settled as the hand's own outcome.
+pub fn settle_insurance_wagers(
+    outcomes: &[HandInsuranceOutcome],
+    round_id: Uuid,
+    insurance: &[HandInsurance],
+) -> Vec<HandSettlement> {
+    outcomes
+        .iter()
+        .filter_map(|(hand_id, outcome)| {
+            let stake = insurance.iter().find_map(|i| match i {
+                (id, Insurance::Wager(stake)) if id == hand_id => Some(*stake),
+                _ => None,
+            })?;
+            let payout = match outcome {
+                InsuranceOutcome::Won(amount) => stake + amount,
+                InsuranceOutcome::Lost(_) => 0,
+                InsuranceOutcome::EvenMoney => return None,
+            };
+            Some((
+                *hand_id,
+                round_id,
+                Settlement {
+                    stake,
+                    payout,
+                    rake: 0,
+                },
+            ))
+        })
+        .collect()
+}
+
+// Settle the side bets in the same terms as the hands, there's one settlement per side bet.
+pub fn settle_side_bet_wagers(
+    outcomes: &[SideBetOutcome],
+    side_bets: &[HandSideBet],
+) -> Vec<HandSettlement> {
+    outcomes
+        .iter()
+        .filter_map(|(hand_id, round_id, kind, result)| {
+            let stake = side_bets
+                .iter()
+                .find(|sb| sb.0 == *hand_id && sb.1 == *kind)
+                .map(|sb| sb.2)?;
+            let payout = match result {
+                SideBetResult::Won(_, amount) => stake + amount,
+                SideBetResult::Lost(_) => 0,
+            };
+            Some((
+                *hand_id,
+                *round_id,
+                Settlement {
+                    stake,
+                    payout,
+                    rake: 0,
+                },
+            ))
+        })
+        .collect()
+}
+
+pub fn get_settlement(
+    hand_id: Uuid,
+    round_id: Uuid,
+    settlements: &[HandSettlement],
+) -> Option<Settlement> {
+    settlements
+        .iter()
+        .find(|s| s.0 == hand_id && s.1 == round_id)
+        .map(|s| s.2)
+}
@@ -0,0 +1,110 @@
+use log::{trace, warn};
+
+use crate::shuffler::{ShuffleSeed, Shuffler};
+use crate::types::*;
+
+// A number of decks shuffled together that a table deals from.
+//
+// Cards are never taken out of the shoe, a CardAllocation is just an index into it.  The shoe is
+// only reshuffled between rounds, once the table's allocations have been archived, so the cards
+// are replaced outright.  A shoe that runs dry in the middle of a round has fresh decks added
+// onto the end instead so that the round's allocations stay valid.
+pub struct Shoe {
+    pub cards: Deck,
+    // The decks that get shuffled together every time the shoe is (re)filled.
+    decks: Deck,
+    shuffler: Box<dyn Shuffler>,
+    // The seed of every shuffle in the shoe along with the index of its first card, empty if
+    // the cards were loaded as is.
+    shuffles: Vec<(ShuffleSeed, usize)>,
+    // Percentage of the freshly shuffled cards that are dealt before the cut card comes out.
+    penetration: u8,
+    next_card: usize,
+    cut_card: usize,
+}
+
+impl Shoe {
+    pub fn new(decks: Deck, penetration: u8, shuffler: Box<dyn Shuffler>) -> Shoe {
+        let mut shoe = Shoe {
+            cards: Vec::new(),
+            decks,
+            shuffler,
+            shuffles: Vec::new(),
+            penetration,
+            next_card: 0,
+            cut_card: 0,
+        };
+        shoe.refill();
+        shoe
+    }
+
+    pub fn card(&self, card_idx: usize) -> &Card {
+        &self.cards[card_idx]
+    }
+
+    // Deal the next card from the shoe, returning its index.  If the shoe has somehow been
+    // emptied in the middle of a round then we have no choice but to top it up immediately.
+    pub fn deal(&mut self) -> usize {
+        if self.next_card >= self.cards.len() {
+            warn!("Shoe has run out of cards mid round, adding fresh decks");
+            self.refill();
+        }
+        let card_idx = self.next_card;
+        self.next_card += 1;
+        card_idx
+    }
+
+    // The index of the card that will be dealt next.
+    pub fn next_card(&self) -> usize {
+        self.next_card
+    }
+
+    // Every shuffle that the cards from the given index onwards were dealt from, along with how
+    // many cards into the shuffle the index is.  This is everything needed to deal the same
+    // cards again, even when the shoe had to be topped up part way through.
+    pub fn shuffles_since(&self, card_idx: usize) -> Vec<(ShuffleSeed, usize)> {
+        self.shuffles
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                self.shuffles
+                    .get(i + 1)
+                    .is_none_or(|next| next.1 > card_idx)
+            })
+            .map(|(_, (seed, start))| (*seed, card_idx.saturating_sub(*start)))
+            .collect()
+    }
+
+    // Once the cut card has come out the shoe is reshuffled before the next round.
+    pub fn is_cut_card_reached(&self) -> bool {
+        self.next_card >= self.cut_card
+    }
+
+    // Only call this between rounds, every card the shoe held is gone afterwards.
+    pub fn reshuffle(&mut self) {
+        trace!("Reshuffling shoe");
+        self.cards.clear();
+        self.shuffles.clear();
+        self.refill();
+    }
+
+    // Replace the shoe with a specific set of cards, for when a table needs to deal a known
+    // sequence of cards.
+    pub fn load(&mut self, deck: Deck) {
+        self.cards = deck;
+        self.shuffles.clear();
+        self.next_card = 0;
+        self.cut_card = self.cards.len() * self.penetration as usize / 100;
+    }
+
+    // Add a fresh shuffle of the decks onto the end of the shoe.
+    fn refill(&mut self) {
+        let mut decks = self.decks.clone();
+        let seed = self.shuffler.shuffle(&mut decks);
+
+        self.shuffles.push((seed, self.cards.len()));
+        self.next_card = self.cards.len();
+        self.cards.extend(decks);
+        self.cut_card = self.next_card + self.decks.len() * self.penetration as usize / 100;
+    }
+}
@@ -0,0 +1,59 @@
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::types::*;
+
+// The seed that a shuffle was made from, shuffling the same cards with the same seed will
+// always produce the same order.
+pub type ShuffleSeed = [u8; 32];
+
+// Something that can shuffle the cards in a shoe.  Every shuffle hands back the seed it used so
+// that it can be recorded and the shuffle reproduced later on.
+pub trait Shuffler: Send {
+    fn shuffle(&mut self, deck: &mut Deck) -> ShuffleSeed;
+}
+
+// Shuffle the deck into the order determined by the seed.
+pub fn shuffle_with_seed(deck: &mut Deck, seed: ShuffleSeed) {
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    deck.shuffle(&mut rng);
+}
+
+// The shuffler used for real games, every shuffle is seeded fresh from the operating system's
+// secure random number generator.
+#[derive(Default)]
+pub struct CryptoShuffler;
+
+impl Shuffler for CryptoShuffler {
+    fn shuffle(&mut self, deck: &mut Deck) -> ShuffleSeed {
+        let mut seed = ShuffleSeed::default();
+        OsRng.fill_bytes(&mut seed);
+        shuffle_with_seed(deck, seed);
+        seed
+    }
+}
+
+// A shuffler that produces the same sequence of shuffles every time it is started from the same
+// seed, for when games need to be reproducible.
+pub struct SeededShuffler {
+    rng: ChaCha20Rng,
+}
+
+impl SeededShuffler {
+    pub fn new(seed: u64) -> SeededShuffler {
+        SeededShuffler {
+            rng: ChaCha20Rng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Shuffler for SeededShuffler {
+    fn shuffle(&mut self, deck: &mut Deck) -> ShuffleSeed {
+        let mut seed = ShuffleSeed::default();
+        self.rng.fill_bytes(&mut seed);
+        shuffle_with_seed(deck, seed);
+        seed
+    }
+}
@@ -0,0 +1,201 @@
+use log::warn;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::shoe::Shoe;
+use crate::types::*;
+use crate::utils::get_dealer_up_card;
+
+// The poker hands that win 21+3.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PokerHand {
+    Flush,
+    Straight,
+    ThreeOfAKind,
+    StraightFlush,
+    SuitedTrips,
+}
+
+// The Perfect Pairs tiers, the closer the two cards match the more the pair pays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pair {
+    // Different colours.
+    Mixed,
+    // The same colour but different suits.
+    Coloured,
+    // The same suit.
+    Perfect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SideBetWin {
+    TwentyOnePlusThree(PokerHand),
+    PerfectPairs(Pair),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SideBetResult {
+    // The winning combination along with the amount won, the wager itself is returned as well.
+    Won(SideBetWin, u32),
+    Lost(u32),
+}
+
+// The result of a side bet for a hand in a round, settled separately to the hand itself.
+pub type SideBetOutcome = (Uuid, Uuid, SideBetKind, SideBetResult);
+
+// A wager that's decided by the initial cards of the round, the player's first two cards along
+// with the dealer's up-card.
+pub trait SideBet {
+    fn evaluate(&self, cards: &[&Card], dealer_up_card: &Card) -> Option<SideBetWin>;
+
+    // What a winning combination pays as a (numerator, denominator) ratio of the wager.
+    fn payout(&self, win: SideBetWin) -> (u32, u32);
+}
+
+// The player's two cards and the dealer's up-card make up a three card poker hand.
+pub struct TwentyOnePlusThree;
+
+impl SideBet for TwentyOnePlusThree {
+    fn evaluate(&self, cards: &[&Card], dealer_up_card: &Card) -> Option<SideBetWin> {
+        let [first, second] = cards else {
+            return None;
+        };
+        let poker_hand = [*first, *second, dealer_up_card];
+
+        let is_flush = poker_hand.iter().all(|c| c.suit == first.suit);
+        let is_trips = poker_hand.iter().all(|c| c.value == first.value);
+        let mut ranks = poker_hand.iter().map(|c| rank(c)).collect::<Vec<_>>();
+        ranks.sort_unstable();
+        // Aces count high as well as low, so A-K-Q is a straight.
+        let is_straight =
+            (ranks[0] + 1 == ranks[1] && ranks[1] + 1 == ranks[2]) || ranks == [1, 12, 13];
+
+        let poker_hand = match (is_flush, is_straight, is_trips) {
+            (true, _, true) => PokerHand::SuitedTrips,
+            (true, true, _) => PokerHand::StraightFlush,
+            (_, _, true) => PokerHand::ThreeOfAKind,
+            (_, true, _) => PokerHand::Straight,
+            (true, _, _) => PokerHand::Flush,
+            _ => return None,
+        };
+        Some(SideBetWin::TwentyOnePlusThree(poker_hand))
+    }
+
+    fn payout(&self, win: SideBetWin) -> (u32, u32) {
+        match win {
+            SideBetWin::TwentyOnePlusThree(PokerHand::Flush) => (5, 1),
+            SideBetWin::TwentyOnePlusThree(PokerHand::Straight) => (10, 1),
+            SideBetWin::TwentyOnePlusThree(PokerHand::ThreeOfAKind) => (30, 1),
+            SideBetWin::TwentyOnePlusThree(PokerHand::StraightFlush) => (40, 1),
+            SideBetWin::TwentyOnePlusThree(PokerHand::SuitedTrips) => (100, 1),
+            _ => (0, 1),
+        }
+    }
+}
+
+// Whether the player's first two cards are a pair.
+pub struct PerfectPairs;
+
+impl SideBet for PerfectPairs {
+    fn evaluate(&self, cards: &[&Card], _dealer_up_card: &Card) -> Option<SideBetWin> {
+        let [first, second] = cards else {
+            return None;
+        };
+        if first.value != second.value {
+            return None;
+        }
+
+        let pair = if first.suit == second.suit {
+            Pair::Perfect
+        } else if is_red(first) == is_red(second) {
+            Pair::Coloured
+        } else {
+            Pair::Mixed
+        };
+        Some(SideBetWin::PerfectPairs(pair))
+    }
+
+    fn payout(&self, win: SideBetWin) -> (u32, u32) {
+        match win {
+            SideBetWin::PerfectPairs(Pair::Mixed) => (6, 1),
+            SideBetWin::PerfectPairs(Pair::Coloured) => (12, 1),
+            SideBetWin::PerfectPairs(Pair::Perfect) => (25, 1),
+            _ => (0, 1),
+        }
+    }
+}
+
+pub fn get_side_bet(kind: SideBetKind) -> Box<dyn SideBet> {
+    match kind {
+        SideBetKind::TwentyOnePlusThree => Box::new(TwentyOnePlusThree),
+        SideBetKind::PerfectPairs => Box::new(PerfectPairs),
+    }
+}
+
+// The rank of a card for making poker hands, aces are low here.
+fn rank(card: &Card) -> u8 {
+    match card.value {
+        CardValue::Ace => 1,
+        CardValue::Jack => 11,
+        CardValue::Queen => 12,
+        CardValue::King => 13,
+        CardValue::Value(v) => v,
+    }
+}
+
+fn is_red(card: &Card) -> bool {
+    matches!(card.suit, Suit::Hearts | Suit::Diamonds)
+}
+
+// Settle every side bet placed at the table against the cards that were just dealt.
+pub fn settle_side_bets(
+    game_id: Uuid,
+    round_id: Uuid,
+    hands: &[Hand],
+    side_bets: &[HandSideBet],
+    allocations: &[CardAllocation],
+    shoes: &HashMap<Uuid, Shoe>,
+) -> Vec<SideBetOutcome> {
+    let Some(dealer_up_card) = get_dealer_up_card(game_id, allocations, shoes) else {
+        warn!(
+            "Unable to settle side bets for {}, no up-card dealt",
+            game_id
+        );
+        return Vec::new();
+    };
+    let shoe = shoes.get(&game_id).expect("Unable to find deck for table");
+
+    side_bets
+        .iter()
+        .filter(|sb| hands.iter().any(|h| h.id == sb.0 && h.dealer == game_id))
+        .map(|(hand_id, kind, wager)| {
+            let cards = allocations
+                .iter()
+                .filter(|a| a.hand == *hand_id)
+                .take(2)
+                .map(|a| shoe.card(a.card_idx))
+                .collect::<Vec<_>>();
+            let side_bet = get_side_bet(*kind);
+            let result = match side_bet.evaluate(&cards, dealer_up_card) {
+                Some(win) => {
+                    let (numerator, denominator) = side_bet.payout(win);
+                    SideBetResult::Won(win, wager * numerator / denominator)
+                }
+                None => SideBetResult::Lost(*wager),
+            };
+            (*hand_id, round_id, *kind, result)
+        })
+        .collect()
+}
+
+pub fn get_side_bet_outcomes(
+    hand_id: Uuid,
+    round_id: Uuid,
+    outcomes: &[SideBetOutcome],
+) -> Vec<(SideBetKind, SideBetResult)> {
+    outcomes
+        .iter()
+        .filter(|o| o.0 == hand_id && o.1 == round_id)
+        .map(|o| (o.2, o.3))
+        .collect()
+}
@@ -1,7 +1,9 @@
 use std::fmt;
 use uuid::Uuid;
 
-#[derive(Clone)]
+use crate::shuffler::ShuffleSeed;
+
+#[derive(Clone, PartialEq)]
 pub enum Suit {
     Hearts,
     Diamonds,
@@ -26,7 +28,7 @@ impl fmt::Display for Suit {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum CardValue {
     Ace,
     King,
@@ -83,12 +85,14 @@ pub struct Hand {
     pub id: Uuid,
     pub player: Uuid,
     pub dealer: Uuid,
+    pub parent: Option<Uuid>, //< the hand this one was split from, if any.
 }
 
 pub struct CardAllocation {
     pub hand: Uuid,
     pub dealer: Uuid, //< this is also dealer's uuid since that is how we identify specific decks.
     pub card_idx: usize,
+    pub face_up: bool, //< the dealer's hole card is dealt face down until their turn.
 }
 
 #[derive(Debug)]
@@ -100,8 +104,14 @@ pub struct Sequence {
 // @todo: I've seen this Hold referenced as "Stand" which I guess makes more sense?
 #[derive(Debug, Clone, Copy)]
 pub enum Action {
-    Hit,
-    Hold,
+    Hit,  //< "Twist" in Pontoon.
+    Hold, //< "Stick" in Pontoon.
+    Split,
+    // In Pontoon this is a "buy", the stake is raised and a card dealt but the hand plays on.
+    Double,
+    Surrender,
+    // Blackjack Switch only, swap the second cards of the seat's two hands.
+    Switch,
 }
 
 #[derive(Debug)]
@@ -110,7 +120,190 @@ pub enum State {
     // as it will mess with the game complete calaculation.
     Holding(u8),
     Bust(u8),
-    BlackJack,
+    // A two card 21 from the initial deal, any other 21 is just Holding(21).
+    Natural,
+    Surrendered(u8),
+    // Reached the table's charlie card count without busting.
+    Charlie(u8),
+    // A 21 that qualifies for one of the variant's bonus payouts.
+    Bonus(Bonus),
+}
+
+// When a player is allowed to give up their hand for half of their stake back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Surrender {
+    None,
+    // Only once the dealer has checked their hand for blackjack.  Without a hole card the dealer
+    // checks at the end of the round, and a surrendered hand loses all of its stake to a natural.
+    Late,
+    // Before the dealer checks for blackjack, so a surrender still stands against one.  A hole
+    // card dealer peeks before anyone plays, which leaves it the same as late surrender there.
+    Early,
+}
+
+// Which starting hands a player is allowed to double down on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DoubleRule {
+    Any,
+    NineToEleven,
+    TenToEleven,
+    // Any total, and on any number of cards rather than just the first two.
+    AnyCards,
+}
+
+// The game a table deals, the variants share the same systems and only differ where their rules
+// do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variant {
+    Classic,
+    // Dealt from decks with the tens removed, a player 21 always beats a dealer 21 and some 21s
+    // are paid a bonus.
+    Spanish21,
+    // Every seat plays two hands and can swap their second cards before playing them.  Naturals
+    // only pay 1:1 and a dealer 22 pushes rather than busts.
+    Switch,
+    // British Pontoon, a five card trick beats everything but a pontoon (a natural) and the
+    // dealer wins ties.
+    Pontoon,
+}
+
+// How closely the suits of a 6-7-8 or 7-7-7 match, the bonus paid grows with it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SuitMatch {
+    Mixed,
+    Suited,
+    Spades,
+}
+
+// The Spanish 21 bonus payouts.  These only apply to a hand that hasn't been doubled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bonus {
+    FiveCard21,
+    SixCard21,
+    // Seven or more cards.
+    SevenCard21,
+    SixSevenEight(SuitMatch),
+    SevenSevenSeven(SuitMatch),
+}
+
+// How the dealer's starting hand is dealt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dealing {
+    // Two cards, one of them face down that the dealer peeks at for blackjack.
+    HoleCard,
+    // European no hole card, the dealer is dealt a single card and draws the second on their
+    // turn.  Everything staked on a hand is lost to a dealer natural unless the table only takes
+    // the original bets (OBO), in which case any doubled or split stakes are returned.
+    NoHoleCard { original_bets_only: bool },
+}
+
+// The house rules that a single table (game) is played under.
+#[derive(Debug, Clone, Copy)]
+pub struct TableRules {
+    pub variant: Variant,
+    // The number of decks shuffled together to deal from.
+    pub deck_count: u8,
+    pub dealing: Dealing,
+    // How far into the shoe, as a percentage, the cut card is placed.
+    pub penetration: u8,
+    // H17 when true, otherwise the dealer stands on all 17s (S17).
+    pub dealer_hits_soft_17: bool,
+    // What a natural pays as a (numerator, denominator) ratio of the stake, ie (3, 2) or (6, 5).
+    pub blackjack_payout: (u32, u32),
+    pub insurance_payout: (u32, u32),
+    pub double: DoubleRule,
+    pub double_after_split: bool,
+    // The maximum number of hands a player can end up with by splitting (and re-splitting) a
+    // single starting hand.
+    pub max_split_hands: u8,
+    // Whether a pair of aces that was the result of a split can be split again.
+    pub resplit_aces: bool,
+    // Split aces receive exactly one more card each and then have to stand.
+    pub split_aces_one_card: bool,
+    pub surrender: Surrender,
+    pub min_bet: u32,
+    pub max_bet: u32,
+    // When set a player hand that reaches this many cards without busting wins automatically,
+    // ie Some(5) for a five card charlie.
+    pub charlie: Option<u8>,
+    // What a charlie pays, as a (numerator, denominator) ratio of the stake.
+    pub charlie_payout: (u32, u32),
+    // The house's commission on the winnings of a hand, as a (numerator, denominator) ratio, ie
+    // (5, 100) for 5%.
+    pub rake: (u32, u32),
+    // The house's expected take from every chip wagered under these rules, as a (numerator,
+    // denominator) ratio, ie (5, 1000) for 0.5%.
+    pub house_edge: (u32, u32),
+    // How long the table waits for bets before dealing without the seats that haven't bet.
+    pub betting_time: chrono::Duration,
+    // How long the players have to decide on insurance, anyone who hasn't by then declines it.
+    pub insurance_time: chrono::Duration,
+}
+
+impl Default for TableRules {
+    fn default() -> Self {
+        TableRules {
+            variant: Variant::Classic,
+            deck_count: 1,
+            dealing: Dealing::HoleCard,
+            penetration: 75,
+            dealer_hits_soft_17: false,
+            blackjack_payout: (3, 2),
+            insurance_payout: (2, 1),
+            double: DoubleRule::Any,
+            double_after_split: true,
+            max_split_hands: 4,
+            resplit_aces: false,
+            split_aces_one_card: true,
+            surrender: Surrender::Late,
+            min_bet: 10,
+            max_bet: 1000,
+            charlie: None,
+            charlie_payout: (1, 1),
+            rake: (0, 1),
+            house_edge: (5, 1000),
+            betting_time: chrono::Duration::seconds(15),
+            insurance_time: chrono::Duration::seconds(15),
+        }
+    }
+}
+
+impl TableRules {
+    pub fn spanish_21() -> TableRules {
+        TableRules {
+            variant: Variant::Spanish21,
+            deck_count: 6,
+            dealer_hits_soft_17: true,
+            double: DoubleRule::AnyCards,
+            surrender: Surrender::Late,
+            house_edge: (4, 1000),
+            ..Default::default()
+        }
+    }
+
+    pub fn pontoon() -> TableRules {
+        TableRules {
+            variant: Variant::Pontoon,
+            dealer_hits_soft_17: true,
+            blackjack_payout: (2, 1),
+            surrender: Surrender::None,
+            charlie: Some(5),
+            charlie_payout: (2, 1),
+            house_edge: (4, 1000),
+            ..Default::default()
+        }
+    }
+
+    pub fn switch() -> TableRules {
+        TableRules {
+            variant: Variant::Switch,
+            deck_count: 6,
+            dealer_hits_soft_17: true,
+            blackjack_payout: (1, 1),
+            house_edge: (6, 1000),
+            ..Default::default()
+        }
+    }
 }
 
 //pair mapping hand to an action
@@ -125,7 +318,71 @@ pub type HandState = (Uuid /*this*/, Uuid /*dealer*/, State);
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Outcome {
     Won(u8),
+    // Won with a natural, which pays out at the table's blackjack bonus rather than 1:1.
+    Natural,
     Lost(u8),
+    // The hand tied with the dealer and the stake is returned.
+    Push(u8),
+    Surrendered,
+    // Won automatically by reaching the table's charlie card count without busting.
+    Charlie(u8),
+    // Won with a 21 that pays the variant's bonus rather than 1:1.
+    Bonus(Bonus),
+}
+
+pub type HandOutcome = (Uuid /*hand*/, Uuid /*round*/, Outcome);
+
+// A player's answer to the insurance offer made when the dealer shows an ace.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Insurance {
+    Declined,
+    Wager(u32),
+    // Only available to a hand holding blackjack, paid out 1:1 straight away instead of risking
+    // a push against a dealer blackjack.
+    EvenMoney,
 }
 
-pub type HandOutcome = (Uuid, Outcome);
+pub type HandInsurance = (Uuid, Insurance);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsuranceOutcome {
+    Won(u32),
+    Lost(u32),
+    EvenMoney,
+}
+
+pub type HandInsuranceOutcome = (Uuid, InsuranceOutcome);
+
+// Pair of hands from the same seat that swapped their second cards.
+pub type HandSwitch = (Uuid, Uuid);
+
+// Pair mapping hand to the amount staked on it.
+pub type HandBet = (Uuid, u32);
+
+// The side bets that can be placed alongside the main bet on a hand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SideBetKind {
+    TwentyOnePlusThree,
+    PerfectPairs,
+}
+
+// A side bet of the kind and amount placed on a hand.
+pub type HandSideBet = (Uuid /*hand*/, SideBetKind, u32);
+
+// A finished round of a game, archived along with everything that happened during it.
+pub struct Round {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    // Every shuffle the round was dealt from along with how many cards into it the round
+    // started, empty if the cards were loaded as is.
+    pub shuffles: Vec<(ShuffleSeed, usize)>,
+    pub hands: Vec<Hand>,
+    pub allocations: Vec<CardAllocation>,
+    pub hand_states: Vec<HandState>,
+    pub sequence: Vec<Sequence>,
+    pub bets: Vec<HandBet>,
+    pub side_bets: Vec<HandSideBet>,
+    pub insurance: Vec<HandInsurance>,
+    pub insurance_outcomes: Vec<HandInsuranceOutcome>,
+    pub switches: Vec<HandSwitch>,
+}
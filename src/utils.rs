@@ -1,7 +1,11 @@
-use log::trace;
+use log::{trace, warn};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::limits::LimitError;
+use crate::settlement::winnings;
+use crate::shoe::Shoe;
+use crate::shuffler::Shuffler;
 use crate::types::*;
 
 pub fn get_dealer(hand_id: Uuid, hands: &[Hand]) -> Uuid {
@@ -32,14 +36,14 @@ pub fn get_hand_value(
     hand_id: Uuid,
     hands: &[Hand],
     allocations: &[CardAllocation],
-    decks: &HashMap<Uuid, Deck>,
+    shoes: &HashMap<Uuid, Shoe>,
 ) -> u8 {
     let dealer = get_dealer(hand_id, hands);
-    let deck = decks.get(&dealer).expect("Unable to find deck");
+    let shoe = shoes.get(&dealer).expect("Unable to find deck");
     let cards = allocations
         .iter()
         .filter(|a| a.hand == hand_id)
-        .map(|a| &deck[a.card_idx])
+        .map(|a| shoe.card(a.card_idx))
         .collect::<Vec<_>>();
 
     trace!("cards in hand: {:?}", cards);
@@ -47,19 +51,40 @@ pub fn get_hand_value(
     hand_value(&cards)
 }
 
+// The value a single card contributes to a hand, counting aces high.
+fn card_value(card: &Card) -> u8 {
+    match card.value {
+        CardValue::Value(v) => v,
+        CardValue::Ace => 11,
+        _ => 10,
+    }
+}
+
+// The value of a hand as far as the players at the table can see, which for the dealer is only
+// their up-card until the hole card is turned over.
+pub fn get_visible_hand_value(
+    hand_id: Uuid,
+    hands: &[Hand],
+    allocations: &[CardAllocation],
+    shoes: &HashMap<Uuid, Shoe>,
+) -> u8 {
+    let dealer = get_dealer(hand_id, hands);
+    let shoe = shoes.get(&dealer).expect("Unable to find deck");
+    let cards = allocations
+        .iter()
+        .filter(|a| a.hand == hand_id && a.face_up)
+        .map(|a| shoe.card(a.card_idx))
+        .collect::<Vec<_>>();
+
+    hand_value(&cards)
+}
+
 fn hand_value(cards: &[&Card]) -> u8 {
-    let mut ace_count = 0;
-    let mut value = cards
+    let ace_count = cards
         .iter()
-        .map(|c| match c.value {
-            CardValue::Value(v) => v,
-            CardValue::Ace => {
-                ace_count += 1;
-                11
-            }
-            _ => 10,
-        })
-        .sum::<u8>();
+        .filter(|c| matches!(c.value, CardValue::Ace))
+        .count();
+    let mut value = cards.iter().map(|c| card_value(c)).sum::<u8>();
     for _ in 0..ace_count {
         if value > 21 {
             value -= 10;
@@ -68,156 +93,658 @@ fn hand_value(cards: &[&Card]) -> u8 {
     value
 }
 
+// A soft hand is one where an ace is still being counted as 11.
+fn is_soft(cards: &[&Card]) -> bool {
+    let hard_value = cards
+        .iter()
+        .map(|c| match c.value {
+            CardValue::Ace => 1,
+            _ => card_value(c),
+        })
+        .sum::<u8>();
+    hard_value != hand_value(cards)
+}
+
+// A standard 52 card deck, ace high down to two in every suit.
 pub fn new_deck() -> Deck {
-    vec![
-        Card::new(Suit::Hearts, CardValue::Ace),
-        Card::new(Suit::Hearts, CardValue::King),
-        Card::new(Suit::Hearts, CardValue::Queen),
-        Card::new(Suit::Hearts, CardValue::Jack),
-        Card::new(Suit::Hearts, CardValue::Value(10)),
-        Card::new(Suit::Hearts, CardValue::Value(9)),
-        Card::new(Suit::Hearts, CardValue::Value(8)),
-        Card::new(Suit::Hearts, CardValue::Value(7)),
-        Card::new(Suit::Hearts, CardValue::Value(6)),
-        Card::new(Suit::Hearts, CardValue::Value(5)),
-        Card::new(Suit::Hearts, CardValue::Value(4)),
-        Card::new(Suit::Hearts, CardValue::Value(3)),
-        Card::new(Suit::Hearts, CardValue::Value(2)),
-        Card::new(Suit::Hearts, CardValue::Value(1)),
-        Card::new(Suit::Diamonds, CardValue::Ace),
-        Card::new(Suit::Diamonds, CardValue::King),
-        Card::new(Suit::Diamonds, CardValue::Queen),
-        Card::new(Suit::Diamonds, CardValue::Jack),
-        Card::new(Suit::Diamonds, CardValue::Value(10)),
-        Card::new(Suit::Diamonds, CardValue::Value(9)),
-        Card::new(Suit::Diamonds, CardValue::Value(8)),
-        Card::new(Suit::Diamonds, CardValue::Value(7)),
-        Card::new(Suit::Diamonds, CardValue::Value(6)),
-        Card::new(Suit::Diamonds, CardValue::Value(5)),
-        Card::new(Suit::Diamonds, CardValue::Value(4)),
-        Card::new(Suit::Diamonds, CardValue::Value(3)),
-        Card::new(Suit::Diamonds, CardValue::Value(2)),
-        Card::new(Suit::Diamonds, CardValue::Value(1)),
-        Card::new(Suit::Clubs, CardValue::Ace),
-        Card::new(Suit::Clubs, CardValue::King),
-        Card::new(Suit::Clubs, CardValue::Queen),
-        Card::new(Suit::Clubs, CardValue::Jack),
-        Card::new(Suit::Clubs, CardValue::Value(10)),
-        Card::new(Suit::Clubs, CardValue::Value(9)),
-        Card::new(Suit::Clubs, CardValue::Value(8)),
-        Card::new(Suit::Clubs, CardValue::Value(7)),
-        Card::new(Suit::Clubs, CardValue::Value(6)),
-        Card::new(Suit::Clubs, CardValue::Value(5)),
-        Card::new(Suit::Clubs, CardValue::Value(4)),
-        Card::new(Suit::Clubs, CardValue::Value(3)),
-        Card::new(Suit::Clubs, CardValue::Value(2)),
-        Card::new(Suit::Clubs, CardValue::Value(1)),
-        Card::new(Suit::Spades, CardValue::Ace),
-        Card::new(Suit::Spades, CardValue::King),
-        Card::new(Suit::Spades, CardValue::Queen),
-        Card::new(Suit::Spades, CardValue::Jack),
-        Card::new(Suit::Spades, CardValue::Value(10)),
-        Card::new(Suit::Spades, CardValue::Value(9)),
-        Card::new(Suit::Spades, CardValue::Value(8)),
-        Card::new(Suit::Spades, CardValue::Value(7)),
-        Card::new(Suit::Spades, CardValue::Value(6)),
-        Card::new(Suit::Spades, CardValue::Value(5)),
-        Card::new(Suit::Spades, CardValue::Value(4)),
-        Card::new(Suit::Spades, CardValue::Value(3)),
-        Card::new(Suit::Spades, CardValue::Value(2)),
-        Card::new(Suit::Spades, CardValue::Value(1)),
-    ]
+    [Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades]
+        .iter()
+        .flat_map(|suit| {
+            [
+                CardValue::Ace,
+                CardValue::King,
+                CardValue::Queen,
+                CardValue::Jack,
+            ]
+            .into_iter()
+            .chain((2..=10).rev().map(CardValue::Value))
+            .map(move |value| Card::new(suit.clone(), value))
+        })
+        .collect()
+}
+
+// The dealer's first card is dealt face up.
+pub fn get_dealer_up_card<'a>(
+    game_id: Uuid,
+    allocations: &[CardAllocation],
+    shoes: &'a HashMap<Uuid, Shoe>,
+) -> Option<&'a Card> {
+    let shoe = shoes.get(&game_id)?;
+    allocations
+        .iter()
+        .find(|a| a.hand == game_id)
+        .map(|a| shoe.card(a.card_idx))
+}
+
+// The dealer only checks their hole card for blackjack when the up-card could make one.
+pub fn should_dealer_peek(up_card: &Card) -> bool {
+    card_value(up_card) >= 10
+}
+
+// Insurance is complete once every hand playing the round has made a decision on it.
+pub fn is_insurance_complete(
+    game_id: Uuid,
+    turn_order: &[Sequence],
+    insurance: &[HandInsurance],
+) -> bool {
+    get_undecided_hands(game_id, turn_order, insurance).is_empty()
+}
+
+// The hands playing the round that have yet to make a decision on insurance.
+pub fn get_undecided_hands(
+    game_id: Uuid,
+    turn_order: &[Sequence],
+    insurance: &[HandInsurance],
+) -> Vec<Uuid> {
+    turn_order
+        .iter()
+        .filter(|s| s.game_id == game_id && s.hand_id != game_id)
+        .filter(|s| !insurance.iter().any(|i| i.0 == s.hand_id))
+        .map(|s| s.hand_id)
+        .collect()
+}
+
+// Settle every insurance decision made at the table once the dealer has peeked at their hand.
+// Insurance pays at the table's insurance payout, even money is paid regardless of what the
+// dealer has.
+pub fn settle_insurance(
+    game_id: Uuid,
+    hands: &[Hand],
+    insurance: &[HandInsurance],
+    dealer_blackjack: bool,
+    rules: &TableRules,
+) -> Vec<HandInsuranceOutcome> {
+    insurance
+        .iter()
+        .filter(|i| hands.iter().any(|h| h.id == i.0 && h.dealer == game_id))
+        .filter_map(|(hand_id, decision)| match decision {
+            Insurance::Declined => None,
+            Insurance::Wager(stake) if dealer_blackjack => Some((
+                *hand_id,
+                InsuranceOutcome::Won(winnings(*stake, rules.insurance_payout)),
+            )),
+            Insurance::Wager(stake) => Some((*hand_id, InsuranceOutcome::Lost(*stake))),
+            Insurance::EvenMoney => Some((*hand_id, InsuranceOutcome::EvenMoney)),
+        })
+        .collect::<Vec<_>>()
+}
+
+pub fn get_insurance_outcome(
+    hand_id: Uuid,
+    insurance_outcomes: &[HandInsuranceOutcome],
+) -> Option<InsuranceOutcome> {
+    insurance_outcomes
+        .iter()
+        .find(|o| o.0 == hand_id)
+        .map(|o| o.1)
+}
+
+// The 48 card deck Spanish 21 is dealt from, a standard deck with the four tens taken out.  The
+// picture cards still count as ten.
+pub fn new_spanish_deck() -> Deck {
+    new_deck()
+        .into_iter()
+        .filter(|c| c.value != CardValue::Value(10))
+        .collect()
+}
+
+// A table deals from a number of standard decks shuffled together, Spanish 21 tables from
+// Spanish decks.
+pub fn new_shoe(rules: &TableRules, shuffler: Box<dyn Shuffler>) -> Shoe {
+    let decks = (0..rules.deck_count)
+        .flat_map(|_| match rules.variant {
+            Variant::Spanish21 => new_spanish_deck(),
+            _ => new_deck(),
+        })
+        .collect::<Vec<_>>();
+    Shoe::new(decks, rules.penetration, shuffler)
+}
+
+// Why a bet was turned away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BetError {
+    MissingHand,
+    // The table isn't taking bets, they can only be placed before the cards are dealt.
+    BettingClosed,
+    AlreadyPlaced,
+    BelowTableMinimum,
+    AboveTableMaximum,
+    InsufficientFunds,
+    // The player's responsible gaming limits don't allow it.
+    Limit(LimitError),
+}
+
+// Why a player couldn't sit down at a table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinError {
+    MissingAccount,
+    MissingTable,
+    Limit(LimitError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DepositError {
+    MissingAccount,
+    Limit(LimitError),
 }
 
 pub fn is_hand_active(hand_id: Uuid, hand_states: &[HandState]) -> bool {
     hand_states.iter().find(|&hs| hs.0 == hand_id).is_none()
 }
 
+// Whether the hand finished play still standing on a total, rather than having gone bust or
+// given up.
+pub fn is_standing(state: &State) -> bool {
+    matches!(
+        state,
+        State::Holding(_) | State::Charlie(_) | State::Bonus(_)
+    )
+}
+
 //@note:  This function is actually not general enough.  Hands needs to be a list of hands
 //  which can be from multiple different games and it should take a number of cards to
 //  allocate to each hand.  It might also need an allocation strategy like sequential or
 //  iterative
 pub fn allocate_cards(
     hands: &[Hand],
-    allocations: &[CardAllocation],
+    shoes: &mut HashMap<Uuid, Shoe>,
     game_id: Uuid,
     count: u8,
+    dealing: Dealing,
 ) -> Vec<CardAllocation> {
-    // Find the current card index into the deck
-    let mut card_idx = allocations.iter().filter(|a| a.dealer == game_id).count();
+    let shoe = shoes
+        .get_mut(&game_id)
+        .expect("Unable to find deck for table");
 
-    // Every hard in the game gets allocated a card
+    // Every hard in the game gets allocated a card, with the dealer's second card (the hole
+    // card) dealt face down.  Without a hole card the dealer only gets the one card.
     let mut allocations = Vec::new();
-    for _ in 0..count {
+    for round in 0..count {
         for h in hands {
+            let is_hole_card = h.id == game_id && round == 1;
+            if is_hole_card && dealing != Dealing::HoleCard {
+                continue;
+            }
             if h.dealer == game_id {
+                let card_idx = shoe.deal();
                 trace!("server: Adding card allocation: {},{}", h.id, card_idx);
                 allocations.push(CardAllocation {
                     card_idx,
                     dealer: game_id,
                     hand: h.id,
+                    face_up: !is_hole_card,
                 });
-                card_idx += 1;
             }
         }
     }
     allocations
 }
 
+// Why an action was turned away.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ActionResolutionError {
     MissingHand,
     MissingDeck,
+    // The hand's game isn't in the middle of being played, ie it's waiting on insurance or for
+    // the next round.
+    GameNotActive,
+    HandFinished,
+    NotYourTurn,
+    // The hand already has an action waiting to be played this turn.
+    ActionPending,
+    // The player doesn't have the chips to cover the extra stake the action puts up.
+    InsufficientFunds,
+    // The action isn't one the hand is allowed to make under the table's rules, ie splitting a
+    // non-pair, doubling on three cards or drawing to split aces.
+    CannotSplit,
+    CannotHit,
+    CannotDouble,
+    CannotSwitch,
+    CannotSurrender,
+    // The player's responsible gaming limits don't allow the extra stake.
+    Limit(LimitError),
 }
 
-//@note: Making the assumpting here that there is not going to be any actions
-//       in this list that are going to end up hitting the same deck and therefore
-//       invalidating the number deck index calculated from the allocations list.
+// Every hit deals the next card in the table's shoe to the hand.
 pub fn process_hit_actions(
     actions: &[HandAction],
     hands: &[Hand],
-    allocations: &[CardAllocation],
+    shoes: &mut HashMap<Uuid, Shoe>,
 ) -> Vec<CardAllocation> {
     actions
         .iter()
         .filter(|(_, action)| matches!(action, Action::Hit))
         .filter_map(|(hand_id, _)| hands.iter().find(|hand| hand.id == *hand_id))
+        .map(|hand| deal_card(hand, shoes))
+        .collect::<Vec<_>>()
+}
+
+// Deal the next card from the shoe of the hand's table to the hand.
+pub fn deal_card(hand: &Hand, shoes: &mut HashMap<Uuid, Shoe>) -> CardAllocation {
+    let card_idx = shoes
+        .get_mut(&hand.dealer)
+        .expect("Unable to find deck for table")
+        .deal();
+    trace!("Adding card allocation: {},{}", hand.id, card_idx);
+    CardAllocation {
+        card_idx,
+        dealer: hand.dealer,
+        hand: hand.id,
+        face_up: true,
+    }
+}
+
+// A hand can be split if it is still in play, holds exactly two cards of the same value and the
+// player hasn't yet reached the table's limit on the number of hands split from the same seat.
+pub fn can_split(
+    hand: &Hand,
+    hands: &[Hand],
+    allocations: &[CardAllocation],
+    shoes: &HashMap<Uuid, Shoe>,
+    hand_states: &[HandState],
+    rules: &TableRules,
+) -> bool {
+    if !is_hand_active(hand.id, hand_states) {
+        return false;
+    }
+
+    let shoe = shoes
+        .get(&hand.dealer)
+        .expect("Unable to find deck for table");
+    let cards = allocations
+        .iter()
+        .filter(|a| a.hand == hand.id)
+        .map(|a| shoe.card(a.card_idx))
+        .collect::<Vec<_>>();
+    if cards.len() != 2 || card_value(cards[0]) != card_value(cards[1]) {
+        return false;
+    }
+
+    let is_aces = matches!(cards[0].value, CardValue::Ace);
+    if is_aces && is_split_hand(hand, hands) && !rules.resplit_aces {
+        return false;
+    }
+
+    let seat_hands = hands
+        .iter()
+        .filter(|h| h.dealer == hand.dealer && h.player == hand.player)
+        .count();
+    seat_hands < rules.max_split_hands as usize
+}
+
+// Returns the new sibling hand for every split action, it is up to the caller to move the second
+// card across and deal both hands their next card.  Splits were checked with can_split when the
+// action was added.
+pub fn process_split_actions(actions: &[HandAction], hands: &[Hand]) -> Vec<Hand> {
+    actions
+        .iter()
+        .filter(|(_, action)| matches!(action, Action::Split))
+        .filter_map(|(hand_id, _)| hands.iter().find(|hand| hand.id == *hand_id))
         .map(|hand| {
-            let card_idx = allocations
-                .iter()
-                .filter(|a| a.dealer == hand.dealer)
-                .count();
-            trace!("Adding card allocation: {},{}", hand.id, card_idx);
-            CardAllocation {
-                card_idx,
+            trace!("Splitting hand {}", hand.id);
+            Hand {
+                id: Uuid::new_v4(),
+                player: hand.player,
                 dealer: hand.dealer,
-                hand: hand.id,
+                parent: Some(hand.id),
             }
         })
         .collect::<Vec<_>>()
 }
 
+// A hand can only be doubled on its first two cards, and then only on the totals the table
+// allows and after a split if the table allows it.
+pub fn can_double(
+    hand: &Hand,
+    hands: &[Hand],
+    allocations: &[CardAllocation],
+    shoes: &HashMap<Uuid, Shoe>,
+    hand_states: &[HandState],
+    rules: &TableRules,
+) -> bool {
+    if !is_hand_active(hand.id, hand_states) {
+        return false;
+    }
+
+    let shoe = shoes
+        .get(&hand.dealer)
+        .expect("Unable to find deck for table");
+    let cards = allocations
+        .iter()
+        .filter(|a| a.hand == hand.id)
+        .map(|a| shoe.card(a.card_idx))
+        .collect::<Vec<_>>();
+    if !rules.double_after_split && is_split_hand(hand, hands) {
+        return false;
+    }
+    if is_one_card_split_aces(hand, hands, allocations, shoes, rules) {
+        return false;
+    }
+
+    // In Pontoon a double is a buy, which can be made on any hand short of a five card trick.
+    if rules.variant == Variant::Pontoon {
+        return cards.len() < 5;
+    }
+
+    if cards.len() != 2 && rules.double != DoubleRule::AnyCards {
+        return false;
+    }
+
+    match rules.double {
+        DoubleRule::Any => true,
+        DoubleRule::NineToEleven => (9..=11).contains(&hand_value(&cards)),
+        DoubleRule::TenToEleven => (10..=11).contains(&hand_value(&cards)),
+        DoubleRule::AnyCards => true,
+    }
+}
+
+// Deal the one card every doubled hand gets, doubles were checked with can_double when the
+// action was added.
+pub fn process_double_actions(
+    actions: &[HandAction],
+    hands: &[Hand],
+    shoes: &mut HashMap<Uuid, Shoe>,
+) -> Vec<CardAllocation> {
+    let doubled_hands = actions
+        .iter()
+        .filter(|(_, action)| matches!(action, Action::Double))
+        .filter_map(|(hand_id, _)| hands.iter().find(|hand| hand.id == *hand_id))
+        .collect::<Vec<_>>();
+
+    doubled_hands
+        .into_iter()
+        .map(|hand| deal_card(hand, shoes))
+        .collect::<Vec<_>>()
+}
+
+// A hand that has finished drawing cards, like a doubled hand after its one extra card or the
+// dealer at the end of their turn, either stands on whatever it has or has gone bust.
+pub fn process_standing_states(
+    hands: &[Hand],
+    card_allocations: &[CardAllocation],
+    shoes: &HashMap<Uuid, Shoe>,
+) -> Vec<HandState> {
+    hands
+        .iter()
+        .map(|h| {
+            let shoe = shoes.get(&h.dealer).expect("Unable to find deck for table");
+            let cards = card_allocations
+                .iter()
+                .filter(|a| a.hand == h.id)
+                .map(|a| shoe.card(a.card_idx))
+                .collect::<Vec<_>>();
+            let value = hand_value(&cards);
+            let state = if value > 21 {
+                State::Bust(value)
+            } else if value == 21 && cards.len() == 2 {
+                // Only possible for a dealer without a hole card drawing their second card.
+                State::Natural
+            } else {
+                State::Holding(value)
+            };
+            (h.id, h.dealer, state)
+        })
+        .collect::<Vec<_>>()
+}
+
+// Split hands are both the hand that was split and any of the hands split off from it.
+pub fn is_split_hand(hand: &Hand, hands: &[Hand]) -> bool {
+    hand.parent.is_some() || hands.iter().any(|h| h.parent == Some(hand.id))
+}
+
+// Split aces that only get the one card each, they can be re-split where the table allows it but
+// never drawn to.
+pub fn is_one_card_split_aces(
+    hand: &Hand,
+    hands: &[Hand],
+    allocations: &[CardAllocation],
+    shoes: &HashMap<Uuid, Shoe>,
+    rules: &TableRules,
+) -> bool {
+    if !rules.split_aces_one_card || !is_split_hand(hand, hands) {
+        return false;
+    }
+    let shoe = shoes
+        .get(&hand.dealer)
+        .expect("Unable to find deck for table");
+    allocations
+        .iter()
+        .find(|a| a.hand == hand.id)
+        .is_some_and(|a| matches!(shoe.card(a.card_idx).value, CardValue::Ace))
+}
+
+// The other hand played from the same seat, only a Blackjack Switch seat has one.
+pub fn get_seat_partner(hand: &Hand, hands: &[Hand]) -> Option<Uuid> {
+    hands
+        .iter()
+        .find(|h| {
+            h.id != hand.id
+                && h.player == hand.player
+                && h.dealer == hand.dealer
+                && h.parent.is_none()
+        })
+        .map(|h| h.id)
+}
+
+// A seat can only switch in its first decision window, while both of its hands are still in play
+// on the two cards they were dealt and haven't already been switched.
+pub fn can_switch(
+    hand: &Hand,
+    hands: &[Hand],
+    allocations: &[CardAllocation],
+    hand_states: &[HandState],
+    switches: &[HandSwitch],
+    rules: &TableRules,
+) -> bool {
+    if rules.variant != Variant::Switch || hand.parent.is_some() {
+        return false;
+    }
+    let Some(partner) = get_seat_partner(hand, hands) else {
+        return false;
+    };
+
+    let seat = [hand.id, partner];
+    let card_count = |hand_id: Uuid| allocations.iter().filter(|a| a.hand == hand_id).count();
+    seat.iter()
+        .all(|id| is_hand_active(*id, hand_states) && card_count(*id) == 2)
+        && !hands
+            .iter()
+            .any(|h| h.parent.is_some_and(|p| seat.contains(&p)))
+        && !switches
+            .iter()
+            .any(|s| seat.contains(&s.0) || seat.contains(&s.1))
+}
+
+// Pair up every seat that's switching, switches were checked with can_switch when the action was
+// added.
+pub fn process_switch_actions(actions: &[HandAction], hands: &[Hand]) -> Vec<HandSwitch> {
+    let mut new_switches: Vec<HandSwitch> = Vec::new();
+    for (hand_id, _) in actions
+        .iter()
+        .filter(|(_, action)| matches!(action, Action::Switch))
+    {
+        // Both hands of a seat asking to switch at once is still only the one switch.
+        if new_switches
+            .iter()
+            .any(|s| s.0 == *hand_id || s.1 == *hand_id)
+        {
+            continue;
+        }
+        let Some(hand) = hands.iter().find(|h| h.id == *hand_id) else {
+            warn!("Unable to find hand {} to switch", hand_id);
+            continue;
+        };
+        if let Some(partner) = get_seat_partner(hand, hands) {
+            new_switches.push((hand.id, partner));
+        }
+    }
+    new_switches
+}
+
+// Surrender is only offered as the very first decision on a hand, and under late surrender only
+// if the dealer didn't turn out to have blackjack.  Without a hole card nobody knows that yet, so
+// a late surrender is settled once the dealer has drawn their second card.
+pub fn can_surrender(
+    hand: &Hand,
+    hands: &[Hand],
+    allocations: &[CardAllocation],
+    hand_states: &[HandState],
+    rules: &TableRules,
+) -> bool {
+    let card_count = allocations.iter().filter(|a| a.hand == hand.id).count();
+    let dealer_blackjack = hand_states
+        .iter()
+        .any(|hs| hs.0 == hand.dealer && matches!(hs.2, State::Natural));
+    let in_window = match rules.surrender {
+        Surrender::None => false,
+        Surrender::Late => !dealer_blackjack,
+        Surrender::Early => true,
+    };
+    in_window
+        && card_count == 2
+        && !is_split_hand(hand, hands)
+        && is_hand_active(hand.id, hand_states)
+}
+
+// Surrenders were checked with can_surrender when the action was added.
+pub fn process_surrender_actions(
+    actions: &[HandAction],
+    hands: &[Hand],
+    allocations: &[CardAllocation],
+    shoes: &HashMap<Uuid, Shoe>,
+) -> Vec<HandState> {
+    actions
+        .iter()
+        .filter(|(_, action)| matches!(action, Action::Surrender))
+        .filter_map(|(hand_id, _)| hands.iter().find(|hand| hand.id == *hand_id))
+        .map(|hand| {
+            let value = get_hand_value(hand.id, hands, allocations, shoes);
+            (hand.id, hand.dealer, State::Surrendered(value))
+        })
+        .collect::<Vec<_>>()
+}
+
+// The dealer has no choices to make, they draw until they reach 17 and then stand, with the
+// table deciding whether they also draw on a soft 17.  If none of the players are left with a
+// hand that the dealer's total matters to then there is no point in drawing at all.
+pub fn play_dealer_hands(
+    active_hands: &[Uuid],
+    hands: &[Hand],
+    allocations: &[CardAllocation],
+    shoes: &mut HashMap<Uuid, Shoe>,
+    hand_states: &[HandState],
+    rules: &HashMap<Uuid, TableRules>,
+) -> Vec<CardAllocation> {
+    let mut dealer_allocations = Vec::new();
+    for dealer in active_hands
+        .iter()
+        .filter_map(|id| hands.iter().find(|h| h.id == *id))
+        .filter(|h| h.id == h.dealer)
+    {
+        let table_rules = rules.get(&dealer.id).cloned().unwrap_or_default();
+
+        // Without a hole card the dealer still has to draw to find out if they have a natural
+        // to push against any player naturals.
+        let no_hole_card = table_rules.dealing != Dealing::HoleCard;
+        let players_standing = hand_states.iter().any(|hs| {
+            hs.1 == dealer.id
+                && hs.0 != dealer.id
+                && match hs.2 {
+                    State::Holding(_) => true,
+                    State::Natural => no_hole_card,
+                    State::Surrendered(_) => {
+                        no_hole_card && table_rules.surrender == Surrender::Late
+                    }
+                    _ => false,
+                }
+        });
+        if !players_standing {
+            trace!("No hands left standing, dealer {} won't draw", dealer.id);
+            continue;
+        }
+
+        let mut card_idxs = allocations
+            .iter()
+            .filter(|a| a.hand == dealer.id)
+            .map(|a| a.card_idx)
+            .collect::<Vec<_>>();
+        loop {
+            let shoe = shoes
+                .get(&dealer.id)
+                .expect("Unable to find deck for table");
+            let cards = card_idxs
+                .iter()
+                .map(|card_idx| shoe.card(*card_idx))
+                .collect::<Vec<_>>();
+            let value = hand_value(&cards);
+            let draw =
+                value < 17 || (value == 17 && table_rules.dealer_hits_soft_17 && is_soft(&cards));
+            if !draw {
+                break;
+            }
+
+            let allocation = deal_card(dealer, shoes);
+            card_idxs.push(allocation.card_idx);
+            dealer_allocations.push(allocation);
+        }
+    }
+    dealer_allocations
+}
+
 pub fn process_hand_states(
     hands: &[Hand],
     card_allocations: &[CardAllocation],
-    decks: &HashMap<Uuid, Deck>,
+    shoes: &HashMap<Uuid, Shoe>,
+    rules: &HashMap<Uuid, TableRules>,
 ) -> Vec<HandState> {
     let mut hand_states = Vec::new();
     for h in hands {
-        let deck = decks.get(&h.dealer).expect("Unable to find deck for table");
+        let table_rules = rules.get(&h.dealer).cloned().unwrap_or_default();
+        let shoe = shoes.get(&h.dealer).expect("Unable to find deck for table");
         //@note: its probably faster to just build the hand values by iterating this once and building
         //  it as we go foldish style and then map that into a hand_state rather than iterate all the
         //  allocations for each hand like this.
         let cards = card_allocations
             .iter()
             .filter(|a| a.hand == h.id)
-            .map(|a| &deck[a.card_idx])
+            .map(|a| shoe.card(a.card_idx))
             .collect::<Vec<_>>();
 
         //@note: its probably better to just not add the actives here rather than strip them out later.
         let hand_value = hand_value(&cards);
+        let is_charlie = table_rules
+            .charlie
+            .is_some_and(|card_count| h.id != h.dealer && cards.len() >= card_count as usize);
+        let bonus = match table_rules.variant {
+            Variant::Spanish21 if h.id != h.dealer => spanish_21_bonus(&cards),
+            _ => None,
+        };
         let state = match hand_value {
+            0..=21 if is_charlie => State::Charlie(hand_value),
             0..=20 => State::Active,
-            21 => State::BlackJack,
+            21 if cards.len() == 2 => State::Natural,
+            // Nothing to be gained from playing on from a 21.
+            21 => bonus.map_or(State::Holding(21), State::Bonus),
             _ => State::Bust(hand_value),
         };
         hand_states.push((h.id, h.dealer, state));
@@ -229,11 +756,94 @@ pub fn process_hand_states(
         .collect()
 }
 
+// The bonus a Spanish 21 hand totalling 21 qualifies for, if any.  A doubled hand is stood by
+// process_standing_states and so never gets here.
+fn spanish_21_bonus(cards: &[&Card]) -> Option<Bonus> {
+    let mut values = cards.iter().map(|c| card_value(c)).collect::<Vec<_>>();
+    values.sort_unstable();
+    let suit_match = || {
+        if cards.iter().all(|c| c.suit == Suit::Spades) {
+            SuitMatch::Spades
+        } else if cards.iter().all(|c| c.suit == cards[0].suit) {
+            SuitMatch::Suited
+        } else {
+            SuitMatch::Mixed
+        }
+    };
+    match values.as_slice() {
+        [6, 7, 8] => Some(Bonus::SixSevenEight(suit_match())),
+        [7, 7, 7] => Some(Bonus::SevenSevenSeven(suit_match())),
+        _ => match cards.len() {
+            5 => Some(Bonus::FiveCard21),
+            6 => Some(Bonus::SixCard21),
+            7.. => Some(Bonus::SevenCard21),
+            _ => None,
+        },
+    }
+}
+
+// What a bonus pays as a (numerator, denominator) ratio of the stake.
+pub fn bonus_payout(bonus: Bonus) -> (u32, u32) {
+    match bonus {
+        Bonus::FiveCard21 => (3, 2),
+        Bonus::SixCard21 => (2, 1),
+        Bonus::SevenCard21 => (3, 1),
+        Bonus::SixSevenEight(SuitMatch::Mixed) | Bonus::SevenSevenSeven(SuitMatch::Mixed) => (3, 2),
+        Bonus::SixSevenEight(SuitMatch::Suited) | Bonus::SevenSevenSeven(SuitMatch::Suited) => {
+            (2, 1)
+        }
+        Bonus::SixSevenEight(SuitMatch::Spades) | Bonus::SevenSevenSeven(SuitMatch::Spades) => {
+            (3, 1)
+        }
+    }
+}
+
+// Determine the states of freshly split hands once each has been dealt its second card.  Split
+// aces are forced to stand on that card when the table plays that way, unless they've drawn
+// another ace that they're allowed to re-split.
+pub fn process_split_states(
+    hands: &[Hand],
+    all_hands: &[Hand],
+    card_allocations: &[CardAllocation],
+    shoes: &HashMap<Uuid, Shoe>,
+    rules: &HashMap<Uuid, TableRules>,
+) -> Vec<HandState> {
+    let mut hand_states = Vec::new();
+    for h in hands {
+        let table_rules = rules.get(&h.dealer).cloned().unwrap_or_default();
+        let shoe = shoes.get(&h.dealer).expect("Unable to find deck for table");
+        let cards = card_allocations
+            .iter()
+            .filter(|a| a.hand == h.id)
+            .map(|a| shoe.card(a.card_idx))
+            .collect::<Vec<_>>();
+
+        let is_split_aces = matches!(cards.first().map(|c| &c.value), Some(CardValue::Ace));
+        if is_split_aces && table_rules.split_aces_one_card {
+            if can_split(h, all_hands, card_allocations, shoes, &[], &table_rules) {
+                continue;
+            }
+            hand_states.push((h.id, h.dealer, State::Holding(hand_value(&cards))));
+        } else {
+            // A 21 made from a split pair is never a natural.
+            let states =
+                process_hand_states(std::slice::from_ref(h), card_allocations, shoes, rules)
+                    .into_iter()
+                    .map(|(hand, dealer, state)| match state {
+                        State::Natural => (hand, dealer, State::Holding(21)),
+                        _ => (hand, dealer, state),
+                    });
+            hand_states.extend(states);
+        }
+    }
+    hand_states
+}
+
 pub fn process_hold_actions(
     hands: &[Hand],
     actions: &[HandAction],
     allocations: &[CardAllocation],
-    decks: &HashMap<Uuid, Deck>,
+    shoes: &HashMap<Uuid, Shoe>,
 ) -> Vec<HandState> {
     actions
         .iter()
@@ -241,7 +851,7 @@ pub fn process_hold_actions(
         .map(|(hand, _)| {
             // Grab the deck for the hand.
             let dealer_id = get_dealer(*hand, hands);
-            let deck = decks
+            let shoe = shoes
                 .get(&dealer_id)
                 .expect("Unable to find deck for table");
 
@@ -249,7 +859,7 @@ pub fn process_hold_actions(
             let cards = allocations
                 .iter()
                 .filter(|a| a.hand == *hand)
-                .map(|a| &deck[a.card_idx])
+                .map(|a| shoe.card(a.card_idx))
                 .collect::<Vec<_>>();
             let value = hand_value(&cards);
 
@@ -263,52 +873,177 @@ pub fn process_hold_actions(
 
 // Iterate all of the HandStates in hand_state, for any HandState for which there is a corosponding
 // dealer HandState determine the HandOutcome and return it.
-pub fn resolve_outcomes(hand_values: &[HandState], outcomes: &[HandOutcome]) -> Vec<HandOutcome> {
+pub fn resolve_outcomes(
+    hand_values: &[HandState],
+    outcomes: &[HandOutcome],
+    rounds: &HashMap<Uuid, Uuid>,
+    hands: &[Hand],
+    rules: &HashMap<Uuid, TableRules>,
+) -> Vec<HandOutcome> {
     hand_values
         .iter()
+        // The dealer doesn't have an outcome against themselves
+        .filter(|h| h.0 != h.1)
+        // Every outcome is for the round currently being played at the hand's table.
+        .filter_map(|h| rounds.get(&h.1).map(|round_id| (h, *round_id)))
         // Check if this particular hand already exists within the outcomes list
-        .filter(|h| !outcomes.iter().any(|o| o.0 == h.0))
-        // Grab the dealer value and if it exist return (hand, dealer) pair,
-        // filter out this hand if the dealer state does not exist.
-        .filter_map(|h| hand_values.iter().find(|hv| hv.0 == h.1).map(|d| (h, d)))
-        // And finally lets determine the outcome.
-        .map(|(h, d)| {
-            let state = match d.2 {
-                State::BlackJack => Outcome::Lost(0),
-                State::Bust(_) => Outcome::Won(22),
-                State::Holding(dealer_value) => {
-                    match h.2 {
-                        State::BlackJack => Outcome::Won(21),
-                        State::Bust(v) => Outcome::Lost(v),
-                        State::Holding(v) => {
-                            if v > dealer_value {
-                                Outcome::Won(v)
-                            } else {
-                                Outcome::Lost(v)
-                            }
+        .filter(|(h, round_id)| !outcomes.iter().any(|o| o.0 == h.0 && o.1 == *round_id))
+        // A surrendered hand, a charlie or a bonus 21 is settled straight away without needing
+        // to wait on the dealer.  A Pontoon five card trick still has to wait to see whether the
+        // dealer has a pontoon, and a late surrender without a hole card to see whether the
+        // dealer has a natural.
+        .filter_map(|(h, round_id)| {
+            let table_rules = rules.get(&h.1).cloned().unwrap_or_default();
+            let no_hole_card = table_rules.dealing != Dealing::HoleCard;
+            match h.2 {
+                State::Surrendered(v)
+                    if no_hole_card && table_rules.surrender == Surrender::Late =>
+                {
+                    hand_values
+                        .iter()
+                        .find(|hv| hv.0 == h.1)
+                        .map(|d| match d.2 {
+                            State::Natural => (h.0, round_id, Outcome::Lost(v)),
+                            _ => (h.0, round_id, Outcome::Surrendered),
+                        })
+                }
+                State::Surrendered(_) => Some((h.0, round_id, Outcome::Surrendered)),
+                State::Charlie(v) if table_rules.variant != Variant::Pontoon => {
+                    Some((h.0, round_id, Outcome::Charlie(v)))
+                }
+                State::Bonus(bonus) => Some((h.0, round_id, Outcome::Bonus(bonus))),
+                _ => hand_values.iter().find(|hv| hv.0 == h.1).map(|d| {
+                    let outcome = resolve_outcome(h, d, table_rules.variant);
+                    let original_bets_only = matches!(
+                        table_rules.dealing,
+                        Dealing::NoHoleCard {
+                            original_bets_only: true
                         }
-                        _ => {
-                            unreachable!("Have reached State::Active for a hand while resolving hand outcomes")
+                    );
+                    let is_split_off = hands
+                        .iter()
+                        .any(|hand| hand.id == h.0 && hand.parent.is_some());
+                    // Under OBO the stake on a hand split off from the original is returned rather
+                    // than lost to a dealer natural, as long as the hand hadn't already gone bust.
+                    match (outcome, &d.2) {
+                        (Outcome::Lost(v), State::Natural)
+                            if original_bets_only && is_split_off && is_standing(&h.2) =>
+                        {
+                            (h.0, round_id, Outcome::Push(v))
                         }
+                        _ => (h.0, round_id, outcome),
                     }
-                },
-               _ => unreachable!("The dealer's hand is still active while attempting to resolve the hand outcomes") 
-            };
-            (h.0, state)
+                }),
+            }
         })
         .collect::<_>()
 }
 
-pub fn is_game_complete(dealer: Uuid, hands: &[Hand], hand_states: &[HandState]) -> bool {
-    // A game is complete if all of the hands associated with it have HandState's.
-    let hand_count = hands.iter().filter(|h| dealer == h.dealer).count();
+// Under OBO only the original bet on a hand is lost to a dealer natural, so any additional stake
+// from doubling down is returned by dropping every bet on the hand after the first.
+pub fn original_bets_only(bets: &[HandBet]) -> Vec<HandBet> {
+    bets.iter()
+        .enumerate()
+        .filter(|(idx, bet)| !bets[..*idx].iter().any(|b| b.0 == bet.0))
+        .map(|(_, bet)| *bet)
+        .collect::<Vec<_>>()
+}
+
+// The value behind a hand's state.
+fn state_value(state: &State) -> u8 {
+    match state {
+        State::Holding(v) | State::Bust(v) | State::Surrendered(v) | State::Charlie(v) => *v,
+        State::Natural | State::Bonus(_) => 21,
+        State::Active => unreachable!("Have reached State::Active for a hand while ranking hands"),
+    }
+}
+
+// How a standing hand ranks under the variant being played, None for a bust hand.  A natural
+// beats any other 21, and in Pontoon a five card trick beats everything except a pontoon.
+fn hand_rank(state: &State, variant: Variant) -> Option<(u8, u8)> {
+    match state {
+        State::Bust(_) => None,
+        State::Natural => Some((2, 21)),
+        State::Charlie(v) if variant == Variant::Pontoon => Some((1, *v)),
+        State::Holding(v) | State::Charlie(v) => Some((0, *v)),
+        State::Bonus(_) => Some((0, 21)),
+        State::Active | State::Surrendered(_) => unreachable!(
+            "The dealer's hand is still active while attempting to resolve the hand outcomes"
+        ),
+    }
+}
+
+// Determine the outcome of a single hand against the dealer's hand.  A bust hand always loses,
+// even if the dealer busts as well, and a tie with the dealer is a push unless the variant says
+// otherwise.
+fn resolve_outcome(h: &HandState, d: &HandState, variant: Variant) -> Outcome {
+    let value = state_value(&h.2);
+    let won = match h.2 {
+        State::Natural => Outcome::Natural,
+        State::Charlie(v) => Outcome::Charlie(v),
+        _ => Outcome::Won(value),
+    };
+    match (hand_rank(&h.2, variant), hand_rank(&d.2, variant)) {
+        (None, _) => Outcome::Lost(value),
+        // In Blackjack Switch a dealer 22 pushes every hand still standing.
+        (Some(_), None) if variant == Variant::Switch && matches!(d.2, State::Bust(22)) => {
+            Outcome::Push(value)
+        }
+        (Some(_), None) => won,
+        // In Spanish 21 a player 21 beats any dealer 21.
+        (Some(_), Some(_))
+            if variant == Variant::Spanish21 && value == 21 && state_value(&d.2) == 21 =>
+        {
+            won
+        }
+        (Some(rank), Some(dealer_rank)) => match rank.cmp(&dealer_rank) {
+            std::cmp::Ordering::Greater => won,
+            // The dealer wins ties in Pontoon.
+            std::cmp::Ordering::Equal if variant == Variant::Pontoon => Outcome::Lost(value),
+            std::cmp::Ordering::Equal => Outcome::Push(value),
+            std::cmp::Ordering::Less => Outcome::Lost(value),
+        },
+    }
+}
+
+pub fn is_game_complete(dealer: Uuid, turn_order: &[Sequence], hand_states: &[HandState]) -> bool {
+    // A game is complete if all of the hands playing the round have HandState's, anyone sitting
+    // the round out doesn't have a place in the turn order.
+    let hand_count = turn_order.iter().filter(|s| dealer == s.game_id).count();
     let state_count = hand_states.iter().filter(|hs| dealer == hs.1).count();
     hand_count == state_count
 }
 
+// The total amount staked on a hand, None if nothing has been bet on it.
+pub fn get_hand_bet(hand_id: Uuid, bets: &[HandBet]) -> Option<u32> {
+    bets.iter()
+        .filter(|b| b.0 == hand_id)
+        .map(|b| b.1)
+        .reduce(|total, stake| total + stake)
+}
+
 // @todo:  I guess we need to keep this "clone" but I dont like it.
+// The outcome of the most recent round the hand has finished.
 pub fn get_hand_outcome(hand_id: Uuid, outcomes: &[HandOutcome]) -> Option<Outcome> {
-    outcomes.iter().find(|o| o.0 == hand_id).map(|o| o.1)
+    outcomes.iter().rev().find(|o| o.0 == hand_id).map(|o| o.2)
+}
+
+// Remove and return all of the items that match the predicate.
+pub fn drain_where<T>(items: &mut Vec<T>, predicate: impl Fn(&T) -> bool) -> Vec<T> {
+    let (drained, kept) = std::mem::take(items).into_iter().partition(predicate);
+    *items = kept;
+    drained
+}
+
+pub fn get_round_outcome(
+    hand_id: Uuid,
+    round_id: Uuid,
+    outcomes: &[HandOutcome],
+) -> Option<Outcome> {
+    outcomes
+        .iter()
+        .find(|o| o.0 == hand_id && o.1 == round_id)
+        .map(|o| o.2)
 }
 
 // @todo: this should really be receiving the game_id rather than the turn_order and
@@ -321,6 +1056,16 @@ pub fn determine_next_hand(
 ) -> Option<Uuid> {
     let game_id = get_game(current_hand_id, hands);
     //is_hand_active(current_hand_id, hands);
+
+    // The dealer only gets their turn once every player hand is finished.
+    let players_finished = turn_order
+        .iter()
+        .filter(|s| s.game_id == game_id && s.hand_id != game_id)
+        .all(|s| !is_hand_active(s.hand_id, hand_states));
+    let can_take_turn = |s: &Sequence| {
+        is_hand_active(s.hand_id, hand_states) && (s.hand_id != game_id || players_finished)
+    };
+
     turn_order
         .iter()
         .cycle()
@@ -331,9 +1076,9 @@ pub fn determine_next_hand(
         // Skip this hand, since we're trying to find the next good active hand
         .skip(1)
         // And now iterate from here until we find a hand that is active
-        .find(|&s| is_hand_active(s.hand_id, hand_states) || s.hand_id == current_hand_id)
+        .find(|&s| can_take_turn(s) || s.hand_id == current_hand_id)
+        .filter(|&s| can_take_turn(s))
         .map(|s| s.hand_id)
-        .and_then(|uid| is_hand_active(uid, hand_states).then_some(uid))
 }
 
 // turn sequence; the order in which players take turns (with the dealer going last)
@@ -0,0 +1,144 @@
+use log::error;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::settlement::HandSettlement;
+use crate::side_bets::{SideBetOutcome, SideBetResult};
+use crate::types::*;
+
+// A player's chips.  Chips staked on a hand that's still in play are reserved, they're still
+// part of the balance but can't be bet again until the hand has been settled.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Account {
+    pub balance: u32,
+    pub reserved: u32,
+}
+
+impl Account {
+    pub fn available(&self) -> u32 {
+        self.balance.checked_sub(self.reserved).unwrap_or_else(|| {
+            error!(
+                "Account has {} reserved out of a balance of {}",
+                self.reserved, self.balance
+            );
+            0
+        })
+    }
+
+    // A transaction that would take the balance or the reserve past what a u32 can hold means a
+    // stake was settled twice or never reserved in the first place.  The chips are clamped
+    // rather than wrapping around and the error is logged.
+    pub fn apply(&mut self, transaction: Transaction) {
+        let clamp = |result: Option<u32>, limit: u32| {
+            result.unwrap_or_else(|| {
+                error!("Unable to apply {:?} to {:?}", transaction, self);
+                limit
+            })
+        };
+        match transaction {
+            Transaction::Deposit(amount) | Transaction::Won(_, amount) => {
+                self.balance = clamp(self.balance.checked_add(amount), u32::MAX)
+            }
+            Transaction::Lost(_, amount) => {
+                self.balance = clamp(self.balance.checked_sub(amount), 0)
+            }
+            Transaction::Reserved(_, amount) => {
+                self.reserved = clamp(self.reserved.checked_add(amount), u32::MAX)
+            }
+            Transaction::Released(_, amount) => {
+                self.reserved = clamp(self.reserved.checked_sub(amount), 0)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transaction {
+    Deposit(u32),
+    // Chips staked on a hand, released again once the stake has been settled or returned.
+    Reserved(Uuid /*hand*/, u32),
+    Released(Uuid /*hand*/, u32),
+    Won(Uuid /*hand*/, u32),
+    Lost(Uuid /*hand*/, u32),
+}
+
+// Pair mapping account to a change in its chips, in the order they happened.
+pub type AccountTransaction = (Uuid, Transaction);
+
+// The account that pays for a hand, the player a hand is dealt to is the account holder.
+pub fn get_account(
+    hand_id: Uuid,
+    hands: &[Hand],
+    accounts: &HashMap<Uuid, Account>,
+) -> Option<Uuid> {
+    hands
+        .iter()
+        .find(|h| h.id == hand_id)
+        .map(|h| h.player)
+        .filter(|player| accounts.contains_key(player))
+}
+
+// Release the stakes on the settled wagers and credit or debit whatever they won or lost.
+pub fn settlement_transactions(
+    settlements: &[HandSettlement],
+    hands: &[Hand],
+    accounts: &HashMap<Uuid, Account>,
+) -> Vec<AccountTransaction> {
+    let mut transactions = Vec::new();
+    for (hand_id, _, settlement) in settlements {
+        let Some(account_id) = get_account(*hand_id, hands, accounts) else {
+            continue;
+        };
+        transactions.push((
+            account_id,
+            Transaction::Released(*hand_id, settlement.stake),
+        ));
+        match settlement.payout.cmp(&settlement.stake) {
+            std::cmp::Ordering::Greater => transactions.push((
+                account_id,
+                Transaction::Won(*hand_id, settlement.payout - settlement.stake),
+            )),
+            std::cmp::Ordering::Less => transactions.push((
+                account_id,
+                Transaction::Lost(*hand_id, settlement.stake - settlement.payout),
+            )),
+            std::cmp::Ordering::Equal => {}
+        }
+    }
+    transactions
+}
+
+pub fn settle_side_bet_outcomes(
+    outcomes: &[SideBetOutcome],
+    hands: &[Hand],
+    side_bets: &[HandSideBet],
+    accounts: &HashMap<Uuid, Account>,
+) -> Vec<AccountTransaction> {
+    let mut transactions = Vec::new();
+    for (hand_id, _, kind, result) in outcomes {
+        let Some(account_id) = get_account(*hand_id, hands, accounts) else {
+            continue;
+        };
+        if let Some(wager) = side_bets
+            .iter()
+            .find(|sb| sb.0 == *hand_id && sb.1 == *kind)
+            .map(|sb| sb.2)
+        {
+            transactions.push((account_id, Transaction::Released(*hand_id, wager)));
+        }
+        let settlement = match result {
+            SideBetResult::Won(_, amount) => Transaction::Won(*hand_id, *amount),
+            SideBetResult::Lost(amount) => Transaction::Lost(*hand_id, *amount),
+        };
+        transactions.push((account_id, settlement));
+    }
+    transactions
+}
+
+pub fn get_transactions(account_id: Uuid, transactions: &[AccountTransaction]) -> Vec<Transaction> {
+    transactions
+        .iter()
+        .filter(|t| t.0 == account_id)
+        .map(|t| t.1)
+        .collect()
+}
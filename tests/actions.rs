@@ -0,0 +1,151 @@
+mod common;
+
+use blackjack::{Action, ActionResolutionError, DataSource, DoubleRule, Surrender, TableRules};
+use common::*;
+use uuid::Uuid;
+
+// Deal a round to a single seat at a table with the given rules.
+fn deal(rules: TableRules, cards: &[u8]) -> (DataSource, Uuid, Uuid) {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(rules);
+    ds.set_deck(game_id, loaded_deck(cards));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    (ds, game_id, hand_id)
+}
+
+#[test]
+fn actions_are_only_taken_from_the_hand_whose_turn_it_is() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 5, 6, 7, 5, 6, 10, 10]));
+    let (_, first) = seat(&mut ds, game_id);
+    let (_, second) = seat(&mut ds, game_id);
+
+    // Nothing can be played until the round is dealt.
+    assert_eq!(
+        ds.add_action(first, Action::Hit),
+        Err(ActionResolutionError::GameNotActive)
+    );
+    ds.place_bet(first, 100).unwrap();
+    ds.place_bet(second, 100).unwrap();
+    tick(&mut ds);
+
+    let playing = current_hand(&ds, game_id).unwrap();
+    let waiting = if playing == first { second } else { first };
+    assert_eq!(
+        ds.add_action(waiting, Action::Hit),
+        Err(ActionResolutionError::NotYourTurn)
+    );
+    assert_eq!(
+        ds.add_action(game_id, Action::Hit),
+        Err(ActionResolutionError::NotYourTurn)
+    );
+    assert_eq!(
+        ds.add_action(Uuid::new_v4(), Action::Hit),
+        Err(ActionResolutionError::MissingHand)
+    );
+
+    ds.add_action(playing, Action::Hold).unwrap();
+    assert_eq!(
+        ds.add_action(playing, Action::Hit),
+        Err(ActionResolutionError::ActionPending)
+    );
+    tick(&mut ds);
+    assert_eq!(
+        ds.add_action(playing, Action::Hit),
+        Err(ActionResolutionError::HandFinished)
+    );
+}
+
+#[test]
+fn only_a_pair_can_be_split() {
+    let (mut ds, _, hand_id) = deal(TableRules::default(), &[10, 8, 7, 9, 10, 10]);
+    assert_eq!(
+        ds.add_action(hand_id, Action::Split),
+        Err(ActionResolutionError::CannotSplit)
+    );
+    assert!(ds.actions.is_empty());
+}
+
+#[test]
+fn doubles_are_only_taken_on_two_cards() {
+    let (mut ds, _, hand_id) = deal(TableRules::default(), &[10, 2, 7, 3, 4, 10]);
+    ds.add_action(hand_id, Action::Hit).unwrap();
+    tick(&mut ds);
+
+    assert_eq!(
+        ds.add_action(hand_id, Action::Double),
+        Err(ActionResolutionError::CannotDouble)
+    );
+    assert_eq!(card_count(&ds, hand_id), 3);
+}
+
+#[test]
+fn doubles_are_only_taken_on_the_totals_the_table_allows() {
+    let rules = TableRules {
+        double: DoubleRule::NineToEleven,
+        ..Default::default()
+    };
+    let (mut ds, _, hand_id) = deal(rules, &[10, 10, 7, 2, 4]);
+    assert_eq!(
+        ds.add_action(hand_id, Action::Double),
+        Err(ActionResolutionError::CannotDouble)
+    );
+}
+
+#[test]
+fn surrender_is_only_offered_as_the_first_decision() {
+    let (mut ds, _, hand_id) = deal(TableRules::default(), &[10, 2, 7, 3, 4, 10]);
+    ds.add_action(hand_id, Action::Hit).unwrap();
+    tick(&mut ds);
+
+    assert_eq!(
+        ds.add_action(hand_id, Action::Surrender),
+        Err(ActionResolutionError::CannotSurrender)
+    );
+}
+
+#[test]
+fn surrender_needs_the_table_to_offer_it() {
+    let rules = TableRules {
+        surrender: Surrender::None,
+        ..Default::default()
+    };
+    let (mut ds, _, hand_id) = deal(rules, &[10, 10, 7, 6]);
+    assert_eq!(
+        ds.add_action(hand_id, Action::Surrender),
+        Err(ActionResolutionError::CannotSurrender)
+    );
+}
+
+#[test]
+fn switch_is_only_played_at_a_switch_table() {
+    let (mut ds, _, hand_id) = deal(TableRules::default(), &[10, 10, 7, 6]);
+    assert_eq!(
+        ds.add_action(hand_id, Action::Switch),
+        Err(ActionResolutionError::CannotSwitch)
+    );
+}
+
+#[test]
+fn switch_is_only_offered_in_the_first_decision_window() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules::switch());
+    ds.set_deck(game_id, loaded_deck(&[9, 2, 3, 8, 4, 5, 2, 2, 2]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    for hand_id in ds.get_seat_hands(hand_id) {
+        ds.place_bet(hand_id, 100).unwrap();
+    }
+    tick(&mut ds);
+
+    let playing = current_hand(&ds, game_id).unwrap();
+    ds.add_action(playing, Action::Hit).unwrap();
+    tick(&mut ds);
+    let playing = current_hand(&ds, game_id).unwrap();
+    assert_eq!(
+        ds.add_action(playing, Action::Switch),
+        Err(ActionResolutionError::CannotSwitch)
+    );
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use blackjack::{Action, DataSource, Outcome, TableRules};
+use common::*;
+use uuid::Uuid;
+
+// Dealer 10 & 9, the player draws 2, 3 & 4 onto their 2 & 3 for a five card 14.
+fn five_card_hand(rules: TableRules) -> (DataSource, Uuid, Uuid) {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(rules);
+    ds.set_deck(game_id, loaded_deck(&[10, 2, 9, 3, 2, 3, 4]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    for _ in 0..3 {
+        if current_hand(&ds, game_id) == Some(hand_id) {
+            ds.add_action(hand_id, Action::Hit).unwrap();
+        }
+        tick(&mut ds);
+    }
+    stand_all(&mut ds, game_id);
+    (ds, account_id, hand_id)
+}
+
+#[test]
+fn five_card_charlie_wins_automatically() {
+    let (ds, account_id, hand_id) = five_card_hand(TableRules {
+        charlie: Some(5),
+        ..Default::default()
+    });
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Charlie(14)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1100);
+}
+
+#[test]
+fn charlie_pays_the_table_charlie_payout() {
+    let (ds, account_id, _) = five_card_hand(TableRules {
+        charlie: Some(5),
+        charlie_payout: (2, 1),
+        ..Default::default()
+    });
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1200);
+}
+
+#[test]
+fn five_cards_are_just_a_total_without_the_rule() {
+    let (ds, account_id, hand_id) = five_card_hand(TableRules::default());
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(14)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 900);
+}
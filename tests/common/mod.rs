@@ -0,0 +1,111 @@
+//
+// Helpers shared by the integration tests that drive a DataSource directly
+//
+#![allow(dead_code)]
+
+use blackjack::{Action, Card, CardValue, DataSource, Deck, GameState, Insurance, Outcome, Suit};
+use uuid::Uuid;
+
+pub fn card(suit: Suit, value: u8) -> Card {
+    match value {
+        11 => Card::new(suit, CardValue::Ace),
+        v => Card::new(suit, CardValue::Value(v)),
+    }
+}
+
+// A deck that deals the given values in order, all hearts with 11 standing in for an ace.  The
+// dealer is dealt first, so a single seat table deals dealer, player, dealer (hole), player and
+// then whatever is drawn after that.
+pub fn loaded_deck(values: &[u8]) -> Deck {
+    values.iter().map(|v| card(Suit::Hearts, *v)).collect()
+}
+
+// Open an account with a 1000 chips and sit it down at the table, returning (account, hand).
+pub fn seat(ds: &mut DataSource, game_id: Uuid) -> (Uuid, Uuid) {
+    let account_id = ds.create_account(1000);
+    let hand_id = ds
+        .join_table(game_id, account_id)
+        .expect("Unable to join table");
+    (account_id, hand_id)
+}
+
+// One pass of the simulation, run in the same order as start_backend.
+pub fn tick(ds: &mut DataSource) {
+    ds.process_limits();
+    ds.process_betting();
+    ds.process_insurance();
+    if !ds.actions.is_empty() || ds.is_dealers_turn() {
+        ds.process_switch_actions();
+        ds.process_split_actions();
+        ds.process_hit_actions();
+        ds.process_double_actions();
+        ds.process_surrender_actions();
+        ds.process_hold_actions();
+        ds.process_dealer_hands();
+        ds.resolve_turn();
+    }
+    ds.process_finished_games();
+}
+
+// The player hand at the table whose turn it is.
+pub fn current_hand(ds: &DataSource, game_id: Uuid) -> Option<Uuid> {
+    ds.active_hands
+        .iter()
+        .find(|id| {
+            ds.hands
+                .iter()
+                .any(|h| h.id == **id && h.dealer == game_id && h.id != game_id)
+        })
+        .cloned()
+}
+
+// Play out whatever is left of the round at the table, every hand declining insurance and
+// standing on whatever it has.
+pub fn stand_all(ds: &mut DataSource, game_id: Uuid) {
+    let round_id = ds
+        .get_current_round(game_id)
+        .expect("No round being played");
+    for _ in 0..100 {
+        if ds.rounds.iter().any(|r| r.id == round_id) {
+            return;
+        }
+        if ds.get_game_state(game_id) == Some(GameState::Insurance) {
+            let hand_ids = ds
+                .hands
+                .iter()
+                .filter(|h| h.dealer == game_id && h.id != game_id)
+                .map(|h| h.id)
+                .collect::<Vec<_>>();
+            for hand_id in hand_ids {
+                ds.add_insurance(hand_id, Insurance::Declined);
+            }
+        }
+        if let Some(hand_id) = current_hand(ds, game_id) {
+            ds.add_action(hand_id, Action::Hold)
+                .expect("Unable to stand");
+        }
+        tick(ds);
+    }
+    panic!("The round at {} never finished", game_id);
+}
+
+pub fn outcome(ds: &DataSource, hand_id: Uuid) -> Option<Outcome> {
+    ds.outcomes
+        .iter()
+        .rev()
+        .find(|o| o.0 == hand_id)
+        .map(|o| o.2)
+}
+
+pub fn card_count(ds: &DataSource, hand_id: Uuid) -> usize {
+    ds.allocations.iter().filter(|a| a.hand == hand_id).count()
+}
+
+// The hand that was split off of the given one.
+pub fn split_from(ds: &DataSource, hand_id: Uuid) -> Uuid {
+    ds.hands
+        .iter()
+        .find(|h| h.parent == Some(hand_id))
+        .map(|h| h.id)
+        .expect("No hand was split off")
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use blackjack::{get_comp_points, DataSource, TableRules};
+use common::*;
+
+#[test]
+fn comp_points_are_average_bet_times_hands_times_house_edge() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules {
+        house_edge: (5, 1000),
+        ..Default::default()
+    });
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 7, 9, 10, 10, 8, 8]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    for _ in 0..2 {
+        ds.place_bet(hand_id, 15).unwrap();
+        tick(&mut ds);
+        stand_all(&mut ds, game_id);
+    }
+
+    // 15 x 0.5% is a theoretical loss of 0.075 chips a hand, 7.5 points at 100 points a chip.
+    let comps = ds
+        .comps
+        .iter()
+        .filter(|c| c.0 == account_id)
+        .map(|c| c.2)
+        .collect::<Vec<_>>();
+    assert_eq!(comps.len(), 2);
+    assert!(comps
+        .iter()
+        .all(|c| c.hands == 1 && c.average_bet() == 15 && c.milli_points == 7500));
+
+    // Average bet 15 x 2 hands x 0.5% x 100, the half points aren't lost along the way.
+    assert_eq!(get_comp_points(account_id, &ds.comps), 15);
+}
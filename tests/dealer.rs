@@ -0,0 +1,72 @@
+mod common;
+
+use blackjack::{Action, DataSource, Outcome, TableRules};
+use common::*;
+use uuid::Uuid;
+
+fn dealer_cards(ds: &DataSource, game_id: Uuid) -> usize {
+    ds.rounds.last().map_or(0, |r| {
+        r.allocations.iter().filter(|a| a.hand == game_id).count()
+    })
+}
+
+#[test]
+fn dealer_draws_to_17() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 2, 8, 3, 4]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(dealer_cards(&ds, game_id), 4);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(18)));
+}
+
+#[test]
+fn dealer_stands_on_soft_17() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[6, 10, 11, 8, 4]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(dealer_cards(&ds, game_id), 2);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(18)));
+}
+
+#[test]
+fn dealer_hits_soft_17() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules {
+        dealer_hits_soft_17: true,
+        ..Default::default()
+    });
+    ds.set_deck(game_id, loaded_deck(&[6, 10, 11, 8, 4]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(dealer_cards(&ds, game_id), 3);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(18)));
+}
+
+#[test]
+fn dealer_skips_drawing_when_every_hand_is_bust() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 6, 6, 10, 5]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    ds.add_action(hand_id, Action::Hit).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(dealer_cards(&ds, game_id), 2);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(26)));
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use blackjack::{Action, ActionResolutionError, DataSource, Outcome};
+use common::*;
+
+#[test]
+fn doubling_deals_one_card_and_doubles_the_stake() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    // Dealer 10 & 7, the player doubles on 11 and draws a 10.
+    ds.set_deck(game_id, loaded_deck(&[10, 5, 7, 6, 10, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    ds.add_action(hand_id, Action::Double).unwrap();
+    tick(&mut ds);
+    assert_eq!(card_count(&ds, hand_id), 3);
+    assert_eq!(ds.get_balance(account_id).unwrap().reserved, 200);
+
+    // The hand is done, it's straight over to the dealer.
+    assert_eq!(current_hand(&ds, game_id), None);
+    tick(&mut ds);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(21)));
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (1200, 0));
+}
+
+#[test]
+fn doubled_hand_can_bust() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 7, 6, 10, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    ds.add_action(hand_id, Action::Double).unwrap();
+    tick(&mut ds);
+    tick(&mut ds);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(26)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 800);
+}
+
+#[test]
+fn double_needs_the_chips_to_match_the_bet() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 5, 7, 6, 10, 10]));
+    let account_id = ds.create_account(150);
+    let hand_id = ds.join_table(game_id, account_id).unwrap();
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    assert_eq!(
+        ds.add_action(hand_id, Action::Double),
+        Err(ActionResolutionError::InsufficientFunds)
+    );
+    assert_eq!(card_count(&ds, hand_id), 2);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use blackjack::{DataSource, Outcome};
+use common::*;
+
+#[test]
+fn hole_card_is_dealt_face_down_until_the_dealers_turn() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[9, 10, 8, 8]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    let dealer_cards = ds
+        .allocations
+        .iter()
+        .filter(|a| a.hand == game_id)
+        .map(|a| a.face_up)
+        .collect::<Vec<_>>();
+    assert_eq!(dealer_cards, vec![true, false]);
+    assert!(ds
+        .allocations
+        .iter()
+        .filter(|a| a.hand == hand_id)
+        .all(|a| a.face_up));
+
+    stand_all(&mut ds, game_id);
+    assert!(ds.rounds[0].allocations.iter().all(|a| a.face_up));
+}
+
+#[test]
+fn dealer_peeks_under_a_ten_and_ends_the_round_on_blackjack() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 11, 9]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    // Nobody gets to act, the round is over as soon as it's dealt.
+    assert_eq!(ds.rounds.len(), 1);
+    assert!(ds.rounds[0].allocations.iter().all(|a| a.face_up));
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(19)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 900);
+}
+
+#[test]
+fn dealer_does_not_peek_under_a_low_card() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[9, 10, 8, 9]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    assert_eq!(current_hand(&ds, game_id), Some(hand_id));
+    assert!(ds.hand_states.iter().all(|hs| hs.0 != game_id));
+}
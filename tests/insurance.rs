@@ -0,0 +1,114 @@
+mod common;
+
+use blackjack::{DataSource, GameState, Insurance, InsuranceOutcome, Outcome, TableRules};
+use chrono::Duration;
+use common::*;
+
+#[test]
+fn insurance_pays_two_to_one_against_a_dealer_blackjack() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[11, 10, 10, 8]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    assert_eq!(ds.get_game_state(game_id), Some(GameState::Insurance));
+
+    assert!(ds.add_insurance(hand_id, Insurance::Wager(50)));
+    tick(&mut ds);
+
+    // The insurance win covers the lost bet.
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(18)));
+    assert!(ds
+        .rounds
+        .iter()
+        .flat_map(|r| r.insurance_outcomes.iter())
+        .any(|o| *o == (hand_id, InsuranceOutcome::Won(100))));
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (1000, 0));
+}
+
+#[test]
+fn declined_insurance_plays_on_when_the_dealer_has_no_blackjack() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[11, 10, 6, 9, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    assert!(ds.add_insurance(hand_id, Insurance::Declined));
+    tick(&mut ds);
+    assert_eq!(ds.get_game_state(game_id), Some(GameState::Active));
+    assert_eq!(current_hand(&ds, game_id), Some(hand_id));
+
+    // Dealer soft 17 stands.
+    stand_all(&mut ds, game_id);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(19)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1100);
+}
+
+#[test]
+fn even_money_is_paid_on_the_spot() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[11, 11, 10, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    assert!(ds.add_insurance(hand_id, Insurance::EvenMoney));
+    tick(&mut ds);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(21)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1100);
+}
+
+#[test]
+fn insurance_is_capped_at_half_the_bet() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[11, 10, 6, 9]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    assert!(!ds.add_insurance(hand_id, Insurance::Wager(60)));
+    // Even money is only on offer to a natural.
+    assert!(!ds.add_insurance(hand_id, Insurance::EvenMoney));
+    assert!(ds.add_insurance(hand_id, Insurance::Wager(50)));
+}
+
+#[test]
+fn insurance_waits_for_every_hand_until_the_time_runs_out() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[11, 10, 6, 9]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    tick(&mut ds);
+    assert_eq!(ds.get_game_state(game_id), Some(GameState::Insurance));
+    assert_eq!(current_hand(&ds, game_id), None);
+}
+
+#[test]
+fn undecided_hands_decline_insurance_once_the_time_runs_out() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules {
+        insurance_time: Duration::zero(),
+        ..Default::default()
+    });
+    ds.set_deck(game_id, loaded_deck(&[11, 10, 6, 9, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    // The player never answered, play carries on as if they'd declined.
+    assert_eq!(ds.get_game_state(game_id), Some(GameState::Active));
+    assert!(ds.insurance.contains(&(hand_id, Insurance::Declined)));
+    assert_eq!(current_hand(&ds, game_id), Some(hand_id));
+    stand_all(&mut ds, game_id);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(19)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1100);
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use blackjack::{Action, DataSource, Dealing, EntryKind, LedgerError, SideBetKind, TableRules};
+use common::*;
+use uuid::Uuid;
+
+// Open an account with nothing in it, deposit and bet 100 on a single seat at a table with the
+// given rules and deal the cards.
+fn deal(rules: TableRules, cards: &[u8]) -> (DataSource, Uuid, Uuid, Uuid) {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(rules);
+    ds.set_deck(game_id, loaded_deck(cards));
+    let account_id = ds.create_account(0);
+    ds.deposit(account_id, 500).unwrap();
+    let hand_id = ds.join_table(game_id, account_id).unwrap();
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    (ds, game_id, account_id, hand_id)
+}
+
+#[test]
+fn winning_hand_with_rake_balances() {
+    let rules = TableRules {
+        rake: (5, 100),
+        ..Default::default()
+    };
+    let (mut ds, game_id, account_id, _) = deal(rules, &[10, 10, 8, 9]);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 595);
+    assert!(ds
+        .ledger
+        .iter()
+        .any(|e| e.kind == EntryKind::Rake && e.amount == 5));
+    assert_eq!(ds.verify_ledger(), Ok(()));
+}
+
+#[test]
+fn push_and_surrender_balance() {
+    let (mut ds, game_id, _, _) = deal(TableRules::default(), &[10, 10, 8, 8]);
+    stand_all(&mut ds, game_id);
+    assert_eq!(ds.verify_ledger(), Ok(()));
+
+    let (mut ds, _, account_id, hand_id) = deal(TableRules::default(), &[10, 10, 9, 6, 10]);
+    ds.add_action(hand_id, Action::Surrender).unwrap();
+    tick(&mut ds);
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 450);
+    assert_eq!(ds.verify_ledger(), Ok(()));
+}
+
+#[test]
+fn split_and_doubled_hands_balance() {
+    let (mut ds, game_id, _, hand_id) = deal(TableRules::default(), &[10, 8, 7, 8, 10, 10]);
+    ds.add_action(hand_id, Action::Split).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+    assert_eq!(ds.verify_ledger(), Ok(()));
+
+    let (mut ds, game_id, account_id, hand_id) = deal(TableRules::default(), &[10, 5, 7, 6, 10]);
+    ds.add_action(hand_id, Action::Double).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 700);
+    assert_eq!(ds.verify_ledger(), Ok(()));
+}
+
+#[test]
+fn stake_returned_under_obo_balances() {
+    let rules = TableRules {
+        dealing: Dealing::NoHoleCard {
+            original_bets_only: true,
+        },
+        ..Default::default()
+    };
+    let (mut ds, game_id, account_id, hand_id) = deal(rules, &[10, 5, 6, 10, 11]);
+    ds.add_action(hand_id, Action::Double).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 400);
+    assert_eq!(ds.verify_ledger(), Ok(()));
+}
+
+#[test]
+fn side_bet_stakes_are_booked_when_placed() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[9, 8, 8, 8]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    assert!(ds.place_side_bet(hand_id, SideBetKind::PerfectPairs, 10));
+    assert!(ds
+        .ledger
+        .iter()
+        .any(|e| e.kind == EntryKind::SideBet && e.hand == Some(hand_id) && e.amount == 10));
+
+    tick(&mut ds);
+    // The stake is booked against the round it was placed for.
+    let round_id = ds.get_current_round(game_id).unwrap();
+    assert!(ds
+        .ledger
+        .iter()
+        .filter(|e| e.kind == EntryKind::SideBet)
+        .all(|e| e.round == Some(round_id)));
+    stand_all(&mut ds, game_id);
+    assert_eq!(ds.verify_ledger(), Ok(()));
+}
+
+#[test]
+fn tampering_is_caught() {
+    let round = || {
+        let (mut ds, game_id, account_id, hand_id) = deal(TableRules::default(), &[10, 10, 8, 9]);
+        stand_all(&mut ds, game_id);
+        let round_id = ds.rounds[0].id;
+        (ds, account_id, hand_id, round_id)
+    };
+
+    let (mut ds, _, hand_id, round_id) = round();
+    let entry = ds
+        .ledger
+        .iter_mut()
+        .find(|e| e.kind == EntryKind::WinPaid)
+        .unwrap();
+    entry.amount += 1;
+    assert_eq!(
+        ds.verify_ledger(),
+        Err(LedgerError::Unbalanced(Some(round_id), Some(hand_id)))
+    );
+
+    let (mut ds, account_id, _, _) = round();
+    ds.accounts.get_mut(&account_id).unwrap().balance += 1;
+    assert_eq!(
+        ds.verify_ledger(),
+        Err(LedgerError::NotConserved {
+            accounts: 601,
+            ledger: 600
+        })
+    );
+
+    let (mut ds, _, hand_id, round_id) = round();
+    ds.settlements[0].2.payout += 1;
+    assert_eq!(
+        ds.verify_ledger(),
+        Err(LedgerError::Unmatched(hand_id, round_id))
+    );
+
+    // An outcome that was never settled is just as wrong as one settled for the wrong amount.
+    let (mut ds, _, hand_id, round_id) = round();
+    ds.settlements.clear();
+    assert_eq!(
+        ds.verify_ledger(),
+        Err(LedgerError::Unmatched(hand_id, round_id))
+    );
+}
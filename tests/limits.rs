@@ -0,0 +1,137 @@
+mod common;
+
+use blackjack::{
+    Action, ActionResolutionError, BetError, DataSource, DepositError, Insurance, JoinError, Limit,
+    LimitError, LIMIT_INCREASE_DELAY_HOURS,
+};
+use chrono::{Duration, Utc};
+use common::*;
+
+#[test]
+fn tightening_a_limit_takes_effect_straight_away() {
+    let mut ds = DataSource::default();
+    let account_id = ds.create_account(0);
+    assert!(ds.set_limit(account_id, Limit::Deposit(500)));
+    assert!(ds.set_limit(account_id, Limit::Deposit(300)));
+
+    assert_eq!(ds.limits[&account_id].deposit, Some(300));
+    assert!(ds.get_pending_limits(account_id).is_empty());
+    assert_eq!(
+        ds.deposit(account_id, 400),
+        Err(DepositError::Limit(LimitError::DepositLimit))
+    );
+}
+
+#[test]
+fn loosening_a_limit_waits_out_the_delay() {
+    let mut ds = DataSource::default();
+    let account_id = ds.create_account(0);
+    ds.set_limit(account_id, Limit::Deposit(500));
+    ds.set_limit(account_id, Limit::Deposit(800));
+    ds.process_limits();
+
+    assert_eq!(ds.limits[&account_id].deposit, Some(500));
+    let pending = ds.get_pending_limits(account_id);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].0, Limit::Deposit(800));
+    let delay = pending[0].1 - Utc::now();
+    assert!(delay > Duration::hours(LIMIT_INCREASE_DELAY_HOURS) - Duration::minutes(1));
+    assert!(delay <= Duration::hours(LIMIT_INCREASE_DELAY_HOURS));
+}
+
+#[test]
+fn a_newer_change_replaces_a_pending_increase() {
+    let mut ds = DataSource::default();
+    let account_id = ds.create_account(0);
+    ds.set_limit(account_id, Limit::Wager(100));
+    ds.set_limit(account_id, Limit::Wager(1000));
+    ds.set_limit(account_id, Limit::Wager(600));
+
+    let pending = ds.get_pending_limits(account_id);
+    assert_eq!(
+        pending.iter().map(|p| p.0).collect::<Vec<_>>(),
+        vec![Limit::Wager(600)]
+    );
+
+    // Tightening it again calls off the increase altogether.
+    ds.set_limit(account_id, Limit::Wager(50));
+    assert!(ds.get_pending_limits(account_id).is_empty());
+    assert_eq!(ds.limits[&account_id].wager, Some(50));
+}
+
+#[test]
+fn an_exclusion_cannot_be_cut_short() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    let account_id = ds.create_account(1000);
+    ds.exclude(account_id, Duration::days(7));
+    let until = ds.limits[&account_id].excluded_until.unwrap();
+
+    ds.exclude(account_id, Duration::days(1));
+    assert_eq!(ds.limits[&account_id].excluded_until, Some(until));
+    assert_eq!(
+        ds.join_table(game_id, account_id),
+        Err(JoinError::Limit(LimitError::Excluded(until)))
+    );
+
+    // It can be extended though.
+    ds.exclude(account_id, Duration::days(30));
+    assert!(ds.limits[&account_id].excluded_until.unwrap() > until);
+}
+
+#[test]
+fn the_session_limit_stops_both_joining_and_betting() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    let other_game_id = ds.add_game();
+    let account_id = ds.create_account(1000);
+    ds.set_limit(account_id, Limit::SessionTime(Duration::zero()));
+
+    // Sitting down starts the session, which is up as soon as it's started.
+    let hand_id = ds.join_table(game_id, account_id).unwrap();
+    assert_eq!(
+        ds.place_bet(hand_id, 100),
+        Err(BetError::Limit(LimitError::SessionTime))
+    );
+    assert_eq!(
+        ds.join_table(other_game_id, account_id),
+        Err(JoinError::Limit(LimitError::SessionTime))
+    );
+}
+
+#[test]
+fn doubles_splits_and_insurance_count_towards_the_wager_limit() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 8, 7, 8]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.set_limit(account_id, Limit::Wager(150));
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    let limited = Err(ActionResolutionError::Limit(LimitError::WagerLimit));
+    assert_eq!(ds.add_action(hand_id, Action::Double), limited);
+    assert_eq!(ds.add_action(hand_id, Action::Split), limited);
+
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[11, 10, 10, 8]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.set_limit(account_id, Limit::Wager(120));
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    assert!(!ds.add_insurance(hand_id, Insurance::Wager(50)));
+    assert!(ds.add_insurance(hand_id, Insurance::Wager(20)));
+}
+
+#[test]
+fn deposits_are_summed_without_overflowing() {
+    let mut ds = DataSource::default();
+    let account_id = ds.create_account(u32::MAX - 10);
+    ds.set_limit(account_id, Limit::Deposit(u32::MAX));
+    assert_eq!(
+        ds.deposit(account_id, 100),
+        Err(DepositError::Limit(LimitError::DepositLimit))
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use blackjack::{Action, DataSource, Outcome};
+use common::*;
+
+#[test]
+fn natural_pays_three_to_two() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 11, 7, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Natural));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1150);
+}
+
+#[test]
+fn multi_card_21_is_not_a_natural() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 5, 7, 6, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    ds.add_action(hand_id, Action::Hit).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(21)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1100);
+}
+
+#[test]
+fn natural_beats_a_multi_card_dealer_21() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    // The dealer draws to 21 on their 16.
+    ds.set_deck(game_id, loaded_deck(&[10, 11, 6, 10, 5]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Natural));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1150);
+}
+
+#[test]
+fn multi_card_21_pushes_a_dealer_21() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 5, 6, 6, 10, 5]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    ds.add_action(hand_id, Action::Hit).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Push(21)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1000);
+}
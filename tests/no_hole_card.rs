@@ -0,0 +1,119 @@
+mod common;
+
+use blackjack::{Action, DataSource, Dealing, Outcome, TableRules};
+use common::*;
+use uuid::Uuid;
+
+fn no_hole_card(original_bets_only: bool) -> TableRules {
+    TableRules {
+        dealing: Dealing::NoHoleCard { original_bets_only },
+        ..Default::default()
+    }
+}
+
+// The player doubles on 11 and draws to 21, only for the dealer to turn their 10 into a natural.
+fn double_into_dealer_natural(rules: TableRules) -> (DataSource, Uuid, Uuid) {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(rules);
+    ds.set_deck(game_id, loaded_deck(&[10, 5, 6, 10, 11]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    ds.add_action(hand_id, Action::Double).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+    (ds, account_id, hand_id)
+}
+
+#[test]
+fn dealer_is_dealt_a_single_card_up_front() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(no_hole_card(false));
+    ds.set_deck(game_id, loaded_deck(&[10, 5, 6, 10, 7]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    assert_eq!(card_count(&ds, game_id), 1);
+    assert_eq!(card_count(&ds, hand_id), 2);
+
+    // The dealer draws their second card once it's their turn.
+    stand_all(&mut ds, game_id);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(11)));
+}
+
+#[test]
+fn doubled_stake_is_lost_to_a_dealer_natural() {
+    let (ds, account_id, hand_id) = double_into_dealer_natural(no_hole_card(false));
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(21)));
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (800, 0));
+}
+
+#[test]
+fn only_the_original_bet_is_lost_under_obo() {
+    let (ds, account_id, hand_id) = double_into_dealer_natural(no_hole_card(true));
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(21)));
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (900, 0));
+}
+
+#[test]
+fn a_busted_double_is_lost_in_full_under_obo() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(no_hole_card(true));
+    // Both seats are dealt 16, the first to act doubles into a bust and the other stands, then
+    // the dealer draws an ace to their 10.
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 10, 6, 6, 10, 11]));
+    let first = seat(&mut ds, game_id);
+    let second = seat(&mut ds, game_id);
+    ds.place_bet(first.1, 100).unwrap();
+    ds.place_bet(second.1, 100).unwrap();
+    tick(&mut ds);
+
+    let doubled = current_hand(&ds, game_id).unwrap();
+    let (doubled_account, standing_account) = if doubled == first.1 {
+        (first.0, second.0)
+    } else {
+        (second.0, first.0)
+    };
+    ds.add_action(doubled, Action::Double).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, doubled), Some(Outcome::Lost(26)));
+    assert_eq!(ds.get_balance(doubled_account).unwrap().balance, 800);
+    assert_eq!(ds.get_balance(standing_account).unwrap().balance, 900);
+    assert_eq!(ds.verify_ledger(), Ok(()));
+}
+
+#[test]
+fn a_busted_split_hand_is_lost_under_obo() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(no_hole_card(true));
+    // The eights draw a 10 and a 5, the split hand hits a 10 and busts, then the dealer draws an
+    // ace to their 10.
+    ds.set_deck(game_id, loaded_deck(&[10, 8, 8, 10, 5, 10, 11]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    ds.add_action(hand_id, Action::Split).unwrap();
+    tick(&mut ds);
+
+    let split_hand = split_from(&ds, hand_id);
+    while let Some(playing) = current_hand(&ds, game_id) {
+        let action = if playing == split_hand && card_count(&ds, split_hand) == 2 {
+            Action::Hit
+        } else {
+            Action::Hold
+        };
+        ds.add_action(playing, action).unwrap();
+        tick(&mut ds);
+    }
+    tick(&mut ds);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(18)));
+    assert_eq!(outcome(&ds, split_hand), Some(Outcome::Lost(23)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 800);
+    assert_eq!(ds.verify_ledger(), Ok(()));
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use blackjack::{Action, DataSource, Outcome, TableRules};
+use common::*;
+use uuid::Uuid;
+
+fn deal(cards: &[u8]) -> (DataSource, Uuid, Uuid, Uuid) {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules::pontoon());
+    ds.set_deck(game_id, loaded_deck(cards));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    (ds, game_id, account_id, hand_id)
+}
+
+#[test]
+fn dealer_wins_ties() {
+    let (mut ds, game_id, account_id, hand_id) = deal(&[10, 10, 8, 8]);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(18)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 900);
+}
+
+#[test]
+fn pontoon_pays_two_to_one() {
+    let (mut ds, game_id, account_id, hand_id) = deal(&[10, 11, 8, 10]);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Natural));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1200);
+}
+
+#[test]
+fn five_card_trick_beats_a_dealer_21() {
+    // The player twists three times to a five card 15, the dealer draws a 5 onto their 16.
+    let (mut ds, game_id, account_id, hand_id) = deal(&[10, 2, 6, 3, 2, 4, 4, 5]);
+    for _ in 0..3 {
+        ds.add_action(hand_id, Action::Hit).unwrap();
+        tick(&mut ds);
+    }
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Charlie(15)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1200);
+}
+
+#[test]
+fn buying_a_card_raises_the_stake_and_plays_on() {
+    let (mut ds, game_id, account_id, hand_id) = deal(&[10, 5, 8, 4, 2, 8]);
+    ds.add_action(hand_id, Action::Double).unwrap();
+    tick(&mut ds);
+
+    assert_eq!(card_count(&ds, hand_id), 3);
+    assert_eq!(ds.get_balance(account_id).unwrap().reserved, 200);
+    assert_eq!(current_hand(&ds, game_id), Some(hand_id));
+
+    // and the hand can keep buying, every buy matching the original stake.
+    ds.add_action(hand_id, Action::Double).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(19)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1300);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use blackjack::{DataSource, Outcome};
+use common::*;
+
+#[test]
+fn tied_totals_push() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 10, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Push(20)));
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (1000, 0));
+}
+
+#[test]
+fn blackjack_against_blackjack_pushes() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    // The dealer peeks under their 10 and finds an ace.
+    ds.set_deck(game_id, loaded_deck(&[10, 11, 11, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    tick(&mut ds);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Push(21)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1000);
+}
+
+#[test]
+fn lower_total_still_loses() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 10, 9]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(19)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 900);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use blackjack::{DataSource, GameState, Outcome};
+use common::*;
+
+#[test]
+fn seats_stay_at_the_table_between_rounds() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 7, 9, 10, 10, 8, 8]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    let first_round = ds.get_current_round(game_id).unwrap();
+    stand_all(&mut ds, game_id);
+
+    // The round is archived and the table goes straight back to taking bets.
+    assert_eq!(ds.rounds.len(), 1);
+    assert_eq!(ds.rounds[0].id, first_round);
+    assert_eq!(ds.get_game_state(game_id), Some(GameState::Betting));
+    assert!(ds.allocations.is_empty());
+    assert!(ds.hand_states.is_empty());
+    assert!(ds.bets.is_empty());
+    assert!(ds.hands.iter().any(|h| h.id == hand_id));
+
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    let second_round = ds.get_current_round(game_id).unwrap();
+    assert_ne!(first_round, second_round);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(ds.rounds.len(), 2);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Push(18)));
+    let outcomes = ds
+        .outcomes
+        .iter()
+        .filter(|o| o.0 == hand_id)
+        .map(|o| (o.1, o.2))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        outcomes,
+        vec![
+            (first_round, Outcome::Won(19)),
+            (second_round, Outcome::Push(18))
+        ]
+    );
+}
+
+#[test]
+fn seats_that_dont_bet_sit_the_round_out() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 7, 9]));
+    let (_, playing) = seat(&mut ds, game_id);
+    let (_, sitting_out) = seat(&mut ds, game_id);
+    ds.place_bet(playing, 100).unwrap();
+    // Betting only closes early once every seat has bet.
+    tick(&mut ds);
+    assert_eq!(ds.get_game_state(game_id), Some(GameState::Betting));
+
+    ds.start_game(game_id);
+    stand_all(&mut ds, game_id);
+    assert_eq!(outcome(&ds, playing), Some(Outcome::Won(19)));
+    assert_eq!(outcome(&ds, sitting_out), None);
+    assert!(ds.rounds[0]
+        .allocations
+        .iter()
+        .all(|a| a.hand != sitting_out));
+}
+
+#[test]
+fn seats_without_an_account_are_dealt_in_once_they_bet() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 7, 9]));
+    let hand_id = ds.add_player(game_id);
+    assert_eq!(ds.get_game_state(game_id), Some(GameState::Betting));
+
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    assert_eq!(ds.get_game_state(game_id), Some(GameState::Active));
+    assert_eq!(card_count(&ds, hand_id), 2);
+
+    stand_all(&mut ds, game_id);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(19)));
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use blackjack::{BetError, CardValue, DataSource, Outcome, TableRules};
+use common::*;
+
+#[test]
+fn bets_have_to_fall_inside_the_table_limits() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules {
+        min_bet: 25,
+        max_bet: 500,
+        ..Default::default()
+    });
+    let (_, hand_id) = seat(&mut ds, game_id);
+
+    assert_eq!(ds.place_bet(hand_id, 10), Err(BetError::BelowTableMinimum));
+    assert_eq!(ds.place_bet(hand_id, 600), Err(BetError::AboveTableMaximum));
+    assert_eq!(ds.place_bet(hand_id, 500), Ok(()));
+}
+
+#[test]
+fn shoe_holds_the_table_deck_count() {
+    let mut ds = DataSource::default();
+    let single_deck = ds.add_game();
+    let six_deck = ds.add_game_with_rules(TableRules {
+        deck_count: 6,
+        ..Default::default()
+    });
+    assert_eq!(ds.shoes[&single_deck].cards.len(), 52);
+    assert_eq!(ds.shoes[&six_deck].cards.len(), 6 * 52);
+
+    // Four of each rank to a deck, and nothing below a two.
+    let cards = &ds.shoes[&six_deck].cards;
+    assert!(cards.iter().all(|c| c.value != CardValue::Value(1)));
+    for value in (2..=10)
+        .map(CardValue::Value)
+        .chain([CardValue::Ace, CardValue::King])
+    {
+        assert_eq!(cards.iter().filter(|c| c.value == value).count(), 6 * 4);
+    }
+}
+
+#[test]
+fn natural_pays_the_table_blackjack_payout() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules {
+        blackjack_payout: (6, 5),
+        ..Default::default()
+    });
+    ds.set_deck(game_id, loaded_deck(&[10, 11, 7, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Natural));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1120);
+}
+
+#[test]
+fn tables_keep_their_own_rules() {
+    let mut ds = DataSource::default();
+    let classic = ds.add_game();
+    let spanish = ds.add_game_with_rules(TableRules::spanish_21());
+    assert_eq!(ds.rules[&classic].deck_count, 1);
+    assert_eq!(ds.rules[&spanish].deck_count, 6);
+    assert!(ds.rules[&spanish].dealer_hits_soft_17);
+}
@@ -12,7 +12,10 @@ use blackjack::Outcome;
 
 mod test_framework {
 
-    use blackjack::{Action, Card, CardValue, Deck, Message, Outcome, Resource, Response, Suit};
+    use blackjack::{
+        Action, BetError, Card, CardValue, Deck, Insurance, Message, Outcome, Resource, Response,
+        Suit,
+    };
     use log::{error, info};
     use std::sync::mpsc;
 
@@ -75,6 +78,8 @@ mod test_framework {
         GetCurrentHand(uuid::Uuid /*game_id*/),
         GetHandValue(uuid::Uuid /*hand_id*/),
         AddAction(uuid::Uuid, blackjack::Action),
+        AddInsurance(uuid::Uuid /*hand_id*/, blackjack::Insurance),
+        PlaceBet(uuid::Uuid /*hand_id*/, u32),
     }
 
     impl TestState {
@@ -89,6 +94,10 @@ mod test_framework {
                 Self::AddAction(current_hand_id, action) => {
                     Message::AddHandAction(*current_hand_id, *action)
                 }
+                Self::AddInsurance(hand_id, decision) => {
+                    Message::AddInsurance(*hand_id, *decision)
+                }
+                Self::PlaceBet(hand_id, amount) => Message::PlaceBet(*hand_id, *amount),
             }
         }
     }
@@ -177,6 +186,20 @@ mod test_framework {
             let received = self.response_rx.try_recv();
             if let Ok(response) = received {
                 match response {
+                    Response::BetRejected(BetError::BettingClosed) => {
+                        //@note: The round was dealt without us, bet again once the next one opens.
+                        self.fsm.set_state(TestState::PlaceBet(self.hand_id, 10));
+                    }
+                    Response::BetRejected(e) => {
+                        panic!("client: Unable to place a bet: {:?}", e);
+                    }
+                    Response::JoinRejected(_) | Response::DepositRejected(_) => {
+                        unimplemented!();
+                    }
+                    Response::ActionRejected(_) => {
+                        //@note: Our action was turned away, find out whose turn it actually is.
+                        self.fsm.set_state(TestState::GetCurrentHand(self.game_id));
+                    }
                     Response::Failed => {
                         //@note: Well shit something bad happened and I dont know what to do about it!
                         unimplemented!();
@@ -197,8 +220,11 @@ mod test_framework {
                             Resource::Player => {
                                 self.hand_id = uid;
                                 info!("client: hand_id={}", self.hand_id);
+                                //@note: Nothing is dealt to the hand until it has a bet on it.
+                                self.fsm.set_state(TestState::PlaceBet(self.hand_id, 10));
+                            }
+                            Resource::Account => {
                                 unimplemented!();
-                                //self.fsm.set_state(TestState::BeginLoop(self.game_id));
                             }
                             Resource::HandAction => {
                                 //@note: So we've sent our action, start the loop again for the next player
@@ -209,7 +235,7 @@ mod test_framework {
                     Response::TableList(tables) => {
                         //@note: tables is a list of tables, not sure if we need to be checking here if
                         //the 'first' table is open or not.
-                        self.game_id = *tables.first().unwrap();
+                        self.game_id = tables.first().unwrap().0;
                     }
                     Response::HandOutcome(outcome) => {
                         // If there is some hand outcome for the test hand then the test is finished
@@ -229,6 +255,29 @@ mod test_framework {
                         self.fsm
                             .set_state(TestState::GetHandValue(self.current_hand_id));
                     }
+                    Response::Seat(_) => {
+                        self.fsm.set_state(TestState::GetHandOutcome(self.hand_id));
+                    }
+                    Response::Round(_) => {
+                        self.fsm.set_state(TestState::GetHandOutcome(self.hand_id));
+                    }
+                    Response::InsuranceOffered => {
+                        //@note: The test player never takes insurance.
+                        self.fsm
+                            .set_state(TestState::AddInsurance(self.hand_id, Insurance::Declined));
+                    }
+                    Response::InsuranceOutcome(_)
+                    | Response::SideBetOutcomes(_)
+                    | Response::Settlement(_)
+                    | Response::Balance(_)
+                    | Response::Transactions(_)
+                    | Response::LedgerEntries(_)
+                    | Response::LedgerVerified(_)
+                    | Response::Limits(_)
+                    | Response::CompPoints(_)
+                    | Response::CompHistory(_) => {
+                        self.fsm.set_state(TestState::GetHandOutcome(self.hand_id));
+                    }
                     Response::HandValue(value) => {
                         info!("client: Hand Value={}", value);
                         if value > 17 {
@@ -0,0 +1,159 @@
+mod common;
+
+use blackjack::{Action, Card, DataSource, Insurance, Settlement, Suit, TableRules};
+use common::*;
+use uuid::Uuid;
+
+// Bet 100 on a single seat at a table with the given rules and deal the cards.
+fn deal(rules: TableRules, deck: Vec<Card>) -> (DataSource, Uuid, Uuid) {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(rules);
+    ds.set_deck(game_id, deck);
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    (ds, game_id, hand_id)
+}
+
+fn settlement(ds: &DataSource, hand_id: Uuid) -> Option<Settlement> {
+    ds.settlements
+        .iter()
+        .rev()
+        .find(|s| s.0 == hand_id)
+        .map(|s| s.2)
+}
+
+fn paid(stake: u32, payout: u32) -> Option<Settlement> {
+    Some(Settlement {
+        stake,
+        payout,
+        rake: 0,
+    })
+}
+
+#[test]
+fn naturals_are_paid_at_the_table_payout() {
+    let (mut ds, game_id, hand_id) = deal(TableRules::default(), loaded_deck(&[10, 11, 7, 10]));
+    stand_all(&mut ds, game_id);
+    assert_eq!(settlement(&ds, hand_id), paid(100, 250));
+
+    let six_to_five = TableRules {
+        blackjack_payout: (6, 5),
+        ..Default::default()
+    };
+    let (mut ds, game_id, hand_id) = deal(six_to_five, loaded_deck(&[10, 11, 7, 10]));
+    stand_all(&mut ds, game_id);
+    assert_eq!(settlement(&ds, hand_id), paid(100, 220));
+    assert_eq!(settlement(&ds, hand_id).unwrap().net(), 120);
+}
+
+#[test]
+fn a_push_pays_back_the_stake() {
+    let (mut ds, game_id, hand_id) = deal(TableRules::default(), loaded_deck(&[10, 10, 8, 8]));
+    stand_all(&mut ds, game_id);
+    assert_eq!(settlement(&ds, hand_id), paid(100, 100));
+    assert_eq!(settlement(&ds, hand_id).unwrap().net(), 0);
+}
+
+#[test]
+fn a_surrender_pays_back_half_the_stake() {
+    let (mut ds, _, hand_id) = deal(TableRules::default(), loaded_deck(&[10, 10, 9, 6, 10]));
+    ds.add_action(hand_id, Action::Surrender).unwrap();
+    tick(&mut ds);
+    assert_eq!(settlement(&ds, hand_id), paid(100, 50));
+}
+
+#[test]
+fn a_charlie_is_paid_at_the_charlie_payout() {
+    let rules = TableRules {
+        charlie: Some(5),
+        charlie_payout: (2, 1),
+        ..Default::default()
+    };
+    let (mut ds, _, hand_id) = deal(rules, loaded_deck(&[10, 2, 7, 2, 2, 2, 3]));
+    for _ in 0..3 {
+        ds.add_action(hand_id, Action::Hit).unwrap();
+        tick(&mut ds);
+    }
+    assert_eq!(settlement(&ds, hand_id), paid(100, 300));
+}
+
+#[test]
+fn spanish_21_bonuses_are_paid_at_their_own_odds() {
+    // A five card 21 pays 3:2.
+    let (mut ds, game_id, hand_id) = deal(
+        TableRules::spanish_21(),
+        loaded_deck(&[10, 2, 9, 3, 4, 5, 7]),
+    );
+    for _ in 0..3 {
+        ds.add_action(hand_id, Action::Hit).unwrap();
+        tick(&mut ds);
+    }
+    stand_all(&mut ds, game_id);
+    assert_eq!(settlement(&ds, hand_id), paid(100, 250));
+
+    // A suited 6-7-8 pays 2:1.
+    let (mut ds, game_id, hand_id) = deal(TableRules::spanish_21(), loaded_deck(&[10, 6, 9, 7, 8]));
+    ds.add_action(hand_id, Action::Hit).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+    assert_eq!(settlement(&ds, hand_id), paid(100, 300));
+
+    // And 3:1 in spades.
+    let deck = vec![
+        card(Suit::Hearts, 10),
+        card(Suit::Spades, 6),
+        card(Suit::Hearts, 9),
+        card(Suit::Spades, 7),
+        card(Suit::Spades, 8),
+    ];
+    let (mut ds, game_id, hand_id) = deal(TableRules::spanish_21(), deck);
+    ds.add_action(hand_id, Action::Hit).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+    assert_eq!(settlement(&ds, hand_id), paid(100, 400));
+}
+
+#[test]
+fn even_money_is_settled_as_a_win() {
+    let (mut ds, _, hand_id) = deal(TableRules::default(), loaded_deck(&[11, 11, 10, 10]));
+    assert!(ds.add_insurance(hand_id, Insurance::EvenMoney));
+    tick(&mut ds);
+    assert_eq!(settlement(&ds, hand_id), paid(100, 200));
+    // There's no insurance wager to settle alongside it.
+    assert!(ds.insurance_settlements.iter().all(|s| s.0 != hand_id));
+}
+
+#[test]
+fn insurance_is_settled_at_two_to_one() {
+    let (mut ds, _, hand_id) = deal(TableRules::default(), loaded_deck(&[11, 10, 10, 8]));
+    assert!(ds.add_insurance(hand_id, Insurance::Wager(50)));
+    tick(&mut ds);
+
+    assert_eq!(settlement(&ds, hand_id), paid(100, 0));
+    assert_eq!(
+        ds.insurance_settlements
+            .iter()
+            .find(|s| s.0 == hand_id)
+            .map(|s| s.2),
+        paid(50, 150)
+    );
+}
+
+#[test]
+fn rake_comes_out_of_the_winnings() {
+    let rules = TableRules {
+        rake: (5, 100),
+        ..Default::default()
+    };
+    let (mut ds, game_id, hand_id) = deal(rules, loaded_deck(&[10, 10, 8, 9]));
+    stand_all(&mut ds, game_id);
+    assert_eq!(
+        settlement(&ds, hand_id),
+        Some(Settlement {
+            stake: 100,
+            payout: 195,
+            rake: 5,
+        })
+    );
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use blackjack::{DataSource, SeededShuffler, Shoe};
+use common::*;
+
+#[test]
+fn cut_card_comes_out_at_the_penetration() {
+    let mut shoe = Shoe::new(loaded_deck(&[2; 20]), 50, Box::new(SeededShuffler::new(1)));
+    for _ in 0..9 {
+        shoe.deal();
+    }
+    assert!(!shoe.is_cut_card_reached());
+    shoe.deal();
+    assert!(shoe.is_cut_card_reached());
+}
+
+#[test]
+fn reshuffle_replaces_the_cards_in_the_shoe() {
+    let mut shoe = Shoe::new(loaded_deck(&[2; 20]), 50, Box::new(SeededShuffler::new(1)));
+    for _ in 0..15 {
+        shoe.deal();
+    }
+    shoe.reshuffle();
+
+    assert_eq!(shoe.cards.len(), 20);
+    assert!(!shoe.is_cut_card_reached());
+    assert_eq!(shoe.deal(), 0);
+}
+
+#[test]
+fn running_dry_mid_round_keeps_the_dealt_cards() {
+    let mut shoe = Shoe::new(loaded_deck(&[2; 4]), 100, Box::new(SeededShuffler::new(1)));
+    let dealt = (0..6).map(|_| shoe.deal()).collect::<Vec<_>>();
+
+    assert_eq!(dealt, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(shoe.cards.len(), 8);
+}
+
+#[test]
+fn long_running_table_never_grows_the_shoe() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    let deck_size = ds.shoes[&game_id].cards.len();
+    let (_, hand_id) = seat(&mut ds, game_id);
+
+    for _ in 0..100 {
+        ds.place_bet(hand_id, 10).unwrap();
+        tick(&mut ds);
+        stand_all(&mut ds, game_id);
+        assert!(ds.shoes[&game_id].cards.len() <= 2 * deck_size);
+    }
+    assert_eq!(ds.rounds.len(), 100);
+}
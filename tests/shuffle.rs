@@ -0,0 +1,104 @@
+mod common;
+
+use blackjack::{shuffle_with_seed, Card, DataSource, Deck, SeededShuffler, Shoe, TableRules};
+use common::*;
+
+fn describe(cards: &[Card]) -> Vec<String> {
+    cards.iter().map(|c| format!("{:?}", c)).collect()
+}
+
+fn describe_shoe(seed: u64) -> Vec<String> {
+    let shoe = Shoe::new(deck(), 75, Box::new(SeededShuffler::new(seed)));
+    describe(&shoe.cards)
+}
+
+fn deck() -> Deck {
+    loaded_deck(&[2, 3, 4, 5, 6, 7, 8, 9, 10, 11])
+}
+
+// Play a round at a table shuffled from the seed, returning the cards it dealt in order.
+fn deal_round(seed: u64) -> Vec<String> {
+    let mut ds = DataSource::default();
+    let game_id =
+        ds.add_game_with_shuffler(TableRules::default(), Box::new(SeededShuffler::new(seed)));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 10).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    let mut allocations = ds.rounds[0].allocations.iter().collect::<Vec<_>>();
+    allocations.sort_by_key(|a| a.card_idx);
+    let shoe = &ds.shoes[&game_id];
+    allocations
+        .iter()
+        .map(|a| format!("{:?}", shoe.card(a.card_idx)))
+        .collect()
+}
+
+#[test]
+fn seeded_shuffler_deals_the_same_round_every_time() {
+    assert_eq!(deal_round(42), deal_round(42));
+    assert_ne!(describe_shoe(42), describe_shoe(43));
+}
+
+#[test]
+fn recorded_seeds_reproduce_the_cards_dealt() {
+    let mut shoe = Shoe::new(deck(), 100, Box::new(SeededShuffler::new(7)));
+    for _ in 0..4 {
+        shoe.deal();
+    }
+    // The shoe runs dry part way through, so the cards come from two shuffles.
+    let dealt = (0..10).map(|_| shoe.deal()).collect::<Vec<_>>();
+    let shuffles = shoe.shuffles_since(dealt[0]);
+    assert_eq!(shuffles.len(), 2);
+    assert_eq!((shuffles[0].1, shuffles[1].1), (4, 0));
+
+    let replayed = shuffles
+        .iter()
+        .flat_map(|(seed, _)| {
+            let mut cards = deck();
+            shuffle_with_seed(&mut cards, *seed);
+            cards
+        })
+        .skip(shuffles[0].1)
+        .take(dealt.len())
+        .collect::<Vec<_>>();
+    let dealt = dealt
+        .iter()
+        .map(|idx| shoe.card(*idx).clone())
+        .collect::<Vec<_>>();
+    assert_eq!(describe(&replayed), describe(&dealt));
+}
+
+#[test]
+fn rounds_record_where_in_the_shuffle_they_started() {
+    let mut ds = DataSource::default();
+    let game_id =
+        ds.add_game_with_shuffler(TableRules::default(), Box::new(SeededShuffler::new(3)));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    for _ in 0..2 {
+        ds.place_bet(hand_id, 10).unwrap();
+        tick(&mut ds);
+        stand_all(&mut ds, game_id);
+    }
+
+    let first = &ds.rounds[0];
+    let second = &ds.rounds[1];
+    assert_eq!(first.shuffles.len(), 1);
+    assert_eq!(first.shuffles[0].1, 0);
+    assert_eq!(second.shuffles[0].0, first.shuffles[0].0);
+    assert_eq!(second.shuffles[0].1, first.allocations.len());
+}
+
+#[test]
+fn loaded_cards_have_no_shuffle_to_record() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 7, 8]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 10).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert!(ds.rounds[0].shuffles.is_empty());
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use blackjack::{
+    Card, CardValue, DataSource, Pair, PerfectPairs, PokerHand, SideBet, SideBetKind,
+    SideBetResult, SideBetWin, Suit, TwentyOnePlusThree,
+};
+use common::*;
+
+fn evaluate(side_bet: &dyn SideBet, player: [Card; 2], dealer_up_card: Card) -> Option<SideBetWin> {
+    side_bet.evaluate(&[&player[0], &player[1]], &dealer_up_card)
+}
+
+#[test]
+fn twenty_one_plus_three_hands() {
+    use Suit::*;
+    let poker_hand = |first, second, up| {
+        evaluate(&TwentyOnePlusThree, [first, second], up).map(|win| match win {
+            SideBetWin::TwentyOnePlusThree(hand) => hand,
+            _ => unreachable!(),
+        })
+    };
+
+    assert_eq!(
+        poker_hand(card(Hearts, 2), card(Hearts, 9), card(Hearts, 5)),
+        Some(PokerHand::Flush)
+    );
+    assert_eq!(
+        poker_hand(card(Hearts, 4), card(Clubs, 5), card(Spades, 6)),
+        Some(PokerHand::Straight)
+    );
+    assert_eq!(
+        poker_hand(card(Hearts, 11), card(Clubs, 11), card(Spades, 11)),
+        Some(PokerHand::ThreeOfAKind)
+    );
+    assert_eq!(
+        poker_hand(card(Clubs, 7), card(Clubs, 8), card(Clubs, 9)),
+        Some(PokerHand::StraightFlush)
+    );
+    assert_eq!(
+        poker_hand(card(Spades, 7), card(Spades, 7), card(Spades, 7)),
+        Some(PokerHand::SuitedTrips)
+    );
+    // Aces play high as well as low.
+    assert_eq!(
+        poker_hand(
+            card(Hearts, 11),
+            Card::new(Clubs, CardValue::King),
+            Card::new(Spades, CardValue::Queen)
+        ),
+        Some(PokerHand::Straight)
+    );
+    assert_eq!(
+        poker_hand(card(Hearts, 2), card(Clubs, 9), card(Spades, 5)),
+        None
+    );
+}
+
+#[test]
+fn perfect_pairs_tiers() {
+    use Suit::*;
+    let up = card(Clubs, 5);
+    let pair = |first, second| evaluate(&PerfectPairs, [first, second], up.clone());
+
+    assert_eq!(
+        pair(card(Hearts, 8), card(Hearts, 8)),
+        Some(SideBetWin::PerfectPairs(Pair::Perfect))
+    );
+    assert_eq!(
+        pair(card(Hearts, 8), card(Diamonds, 8)),
+        Some(SideBetWin::PerfectPairs(Pair::Coloured))
+    );
+    assert_eq!(
+        pair(card(Hearts, 8), card(Spades, 8)),
+        Some(SideBetWin::PerfectPairs(Pair::Mixed))
+    );
+    assert_eq!(pair(card(Hearts, 8), card(Hearts, 9)), None);
+    assert_eq!(
+        PerfectPairs.payout(SideBetWin::PerfectPairs(Pair::Perfect)),
+        (25, 1)
+    );
+}
+
+#[test]
+fn side_bets_are_settled_on_the_initial_deal() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    // A perfect pair of eights that makes a flush with the dealer's 9.
+    ds.set_deck(game_id, loaded_deck(&[9, 8, 8, 8]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    assert!(ds.place_side_bet(hand_id, SideBetKind::PerfectPairs, 10));
+    assert!(ds.place_side_bet(hand_id, SideBetKind::TwentyOnePlusThree, 10));
+    assert_eq!(ds.get_balance(account_id).unwrap().reserved, 120);
+    tick(&mut ds);
+
+    let results = ds
+        .side_bet_outcomes
+        .iter()
+        .filter(|o| o.0 == hand_id)
+        .map(|o| (o.2, o.3))
+        .collect::<Vec<_>>();
+    assert!(results.contains(&(
+        SideBetKind::PerfectPairs,
+        SideBetResult::Won(SideBetWin::PerfectPairs(Pair::Perfect), 250)
+    )));
+    assert!(results.contains(&(
+        SideBetKind::TwentyOnePlusThree,
+        SideBetResult::Won(SideBetWin::TwentyOnePlusThree(PokerHand::Flush), 50)
+    )));
+    // The side bets are paid before the hand has even been played.
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1300);
+
+    stand_all(&mut ds, game_id);
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1200);
+}
+
+#[test]
+fn side_bets_can_only_be_placed_while_betting() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[9, 8, 8, 8]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    assert!(ds.place_side_bet(hand_id, SideBetKind::PerfectPairs, 10));
+    // Only the one of each kind.
+    assert!(!ds.place_side_bet(hand_id, SideBetKind::PerfectPairs, 10));
+    tick(&mut ds);
+
+    assert!(!ds.place_side_bet(hand_id, SideBetKind::TwentyOnePlusThree, 10));
+}
+
+#[test]
+fn side_bets_need_a_main_bet() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[9, 8, 8, 8]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    let (_, playing) = seat(&mut ds, game_id);
+    ds.place_bet(playing, 100).unwrap();
+
+    // The seat would sit the round out without being dealt anything to settle the side bet on.
+    assert!(!ds.place_side_bet(hand_id, SideBetKind::PerfectPairs, 50));
+    ds.start_game(game_id);
+    stand_all(&mut ds, game_id);
+
+    assert!(ds.side_bet_outcomes.iter().all(|o| o.0 != hand_id));
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (1000, 0));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use blackjack::{Action, Bonus, CardValue, DataSource, Outcome, SuitMatch, TableRules};
+use common::*;
+use uuid::Uuid;
+
+// Deal the cards at a Spanish 21 table and hit the player the given number of times.
+fn play(cards: &[u8], hits: usize) -> (DataSource, Uuid, Uuid, Uuid) {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules::spanish_21());
+    ds.set_deck(game_id, loaded_deck(cards));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    for _ in 0..hits {
+        ds.add_action(hand_id, Action::Hit).unwrap();
+        tick(&mut ds);
+    }
+    (ds, game_id, account_id, hand_id)
+}
+
+#[test]
+fn spanish_shoe_has_no_tens() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules::spanish_21());
+    let cards = &ds.shoes[&game_id].cards;
+    assert_eq!(cards.len(), 6 * 48);
+    assert!(cards.iter().all(|c| c.value != CardValue::Value(10)));
+    // The picture cards are still there.
+    assert_eq!(
+        cards.iter().filter(|c| c.value == CardValue::King).count(),
+        6 * 4
+    );
+}
+
+#[test]
+fn player_21_beats_a_dealer_21() {
+    // The dealer hits their 16 to 21.
+    let (mut ds, game_id, account_id, hand_id) = play(&[10, 5, 6, 6, 10, 5], 1);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(21)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1100);
+}
+
+#[test]
+fn five_card_21_pays_a_bonus() {
+    let (mut ds, game_id, account_id, hand_id) = play(&[10, 2, 9, 3, 4, 5, 7], 3);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(
+        outcome(&ds, hand_id),
+        Some(Outcome::Bonus(Bonus::FiveCard21))
+    );
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1150);
+}
+
+#[test]
+fn suited_six_seven_eight_pays_two_to_one() {
+    let (mut ds, game_id, account_id, hand_id) = play(&[10, 6, 9, 7, 8], 1);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(
+        outcome(&ds, hand_id),
+        Some(Outcome::Bonus(Bonus::SixSevenEight(SuitMatch::Suited)))
+    );
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1200);
+}
+
+#[test]
+fn doubling_is_allowed_on_any_number_of_cards() {
+    let (mut ds, game_id, account_id, hand_id) = play(&[10, 2, 7, 3, 4, 10], 1);
+    ds.add_action(hand_id, Action::Double).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    // A doubled 19 beats the dealer's 17 for the doubled stake.
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(19)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1200);
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use blackjack::{Action, ActionResolutionError, DataSource, Outcome, TableRules};
+use common::*;
+
+#[test]
+fn pair_splits_into_two_hands_with_their_own_stakes() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    // Dealer 10 & 7, the player is dealt a pair of eights and draws a 10 onto each of them.
+    ds.set_deck(game_id, loaded_deck(&[10, 8, 7, 8, 10, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    ds.add_action(hand_id, Action::Split).unwrap();
+    tick(&mut ds);
+
+    let split_hand = split_from(&ds, hand_id);
+    assert_eq!(card_count(&ds, hand_id), 2);
+    assert_eq!(card_count(&ds, split_hand), 2);
+    assert!(ds.bets.contains(&(hand_id, 100)));
+    assert!(ds.bets.contains(&(split_hand, 100)));
+    assert_eq!(ds.get_balance(account_id).unwrap().reserved, 200);
+
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(18)));
+    assert_eq!(outcome(&ds, split_hand), Some(Outcome::Won(18)));
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (1200, 0));
+
+    // The split hand goes with the round, the seat keeps playing the hand it sat down with.
+    assert!(ds.hands.iter().all(|h| h.id != split_hand));
+    assert!(ds.rounds[0].hands.iter().any(|h| h.id == split_hand));
+}
+
+#[test]
+fn split_hands_are_settled_separately() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    // Dealer 10 & 8, the nines draw a 10 and a 5.
+    ds.set_deck(game_id, loaded_deck(&[10, 9, 8, 9, 10, 5]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 50).unwrap();
+    tick(&mut ds);
+    ds.add_action(hand_id, Action::Split).unwrap();
+    tick(&mut ds);
+    let split_hand = split_from(&ds, hand_id);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Won(19)));
+    assert_eq!(outcome(&ds, split_hand), Some(Outcome::Lost(14)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1000);
+}
+
+#[test]
+fn split_aces_are_dealt_one_card_each() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 11, 7, 11, 5, 9]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    ds.add_action(hand_id, Action::Split).unwrap();
+    tick(&mut ds);
+
+    // Neither hand gets to act again, the round plays straight through to the dealer.
+    let split_hand = split_from(&ds, hand_id);
+    assert_eq!(current_hand(&ds, game_id), None);
+    tick(&mut ds);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(16)));
+    assert_eq!(outcome(&ds, split_hand), Some(Outcome::Won(20)));
+}
+
+#[test]
+fn pairs_can_be_resplit_up_to_the_table_limit() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules {
+        max_split_hands: 3,
+        ..Default::default()
+    });
+    // Every split draws another eight.
+    ds.set_deck(game_id, loaded_deck(&[10, 8, 7, 8, 8, 8, 8, 8, 8, 8]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    ds.add_action(hand_id, Action::Split).unwrap();
+    tick(&mut ds);
+
+    let playing = current_hand(&ds, game_id).unwrap();
+    ds.add_action(playing, Action::Split).unwrap();
+    tick(&mut ds);
+    assert_eq!(ds.hands.iter().filter(|h| h.parent.is_some()).count(), 2);
+
+    // The seat is now playing three hands, which is as many as the table allows.
+    let playing = current_hand(&ds, game_id).unwrap();
+    assert_eq!(
+        ds.add_action(playing, Action::Split),
+        Err(ActionResolutionError::CannotSplit)
+    );
+}
+
+#[test]
+fn split_aces_stand_on_a_third_ace_without_resplitting() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    // The hand that was split draws another ace, the split hand a 9.
+    ds.set_deck(game_id, loaded_deck(&[10, 11, 7, 11, 11, 9]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    ds.add_action(hand_id, Action::Split).unwrap();
+    tick(&mut ds);
+
+    let split_hand = split_from(&ds, hand_id);
+    assert_eq!(
+        ds.add_action(hand_id, Action::Hit),
+        Err(ActionResolutionError::HandFinished)
+    );
+    assert_eq!(card_count(&ds, hand_id), 2);
+    tick(&mut ds);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(12)));
+    assert_eq!(outcome(&ds, split_hand), Some(Outcome::Won(20)));
+}
+
+#[test]
+fn resplit_aces_can_only_be_split_again_or_stood() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules {
+        resplit_aces: true,
+        ..Default::default()
+    });
+    ds.set_deck(game_id, loaded_deck(&[10, 11, 7, 11, 11, 9, 5, 6]));
+    let (_, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    ds.add_action(hand_id, Action::Split).unwrap();
+    tick(&mut ds);
+
+    assert_eq!(current_hand(&ds, game_id), Some(hand_id));
+    assert_eq!(
+        ds.add_action(hand_id, Action::Hit),
+        Err(ActionResolutionError::CannotHit)
+    );
+    assert_eq!(
+        ds.add_action(hand_id, Action::Double),
+        Err(ActionResolutionError::CannotDouble)
+    );
+    ds.add_action(hand_id, Action::Split).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    // Every one of the aces was dealt the one card.
+    let round = &ds.rounds[0];
+    let aces = round
+        .hands
+        .iter()
+        .filter(|h| h.id != game_id)
+        .collect::<Vec<_>>();
+    assert_eq!(aces.len(), 3);
+    assert!(aces
+        .iter()
+        .all(|h| round.allocations.iter().filter(|a| a.hand == h.id).count() == 2));
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use blackjack::{
+    Action, ActionResolutionError, DataSource, Dealing, Outcome, Surrender, TableRules,
+};
+use common::*;
+use uuid::Uuid;
+
+#[test]
+fn late_surrender_returns_half_the_stake() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 9, 6, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    ds.add_action(hand_id, Action::Surrender).unwrap();
+    tick(&mut ds);
+    tick(&mut ds);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Surrendered));
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (950, 0));
+}
+
+#[test]
+fn early_surrender_stands_against_a_dealer_natural() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules {
+        dealing: Dealing::NoHoleCard {
+            original_bets_only: false,
+        },
+        surrender: Surrender::Early,
+        ..Default::default()
+    });
+    // The dealer only draws their second card, an ace, once the player has surrendered.
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 6, 11]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    ds.add_action(hand_id, Action::Surrender).unwrap();
+    tick(&mut ds);
+    tick(&mut ds);
+
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Surrendered));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 950);
+}
+
+#[test]
+fn early_surrender_at_a_hole_card_table_comes_after_the_peek() {
+    let early = TableRules {
+        surrender: Surrender::Early,
+        ..Default::default()
+    };
+
+    // The dealer peeks at their ace straight away, so there's no chance to surrender.
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(early);
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 11, 6]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    assert_eq!(
+        ds.add_action(hand_id, Action::Surrender),
+        Err(ActionResolutionError::GameNotActive)
+    );
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(16)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 900);
+
+    // Otherwise it's no different to a late surrender.
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(early);
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 9, 6, 10]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+    ds.add_action(hand_id, Action::Surrender).unwrap();
+    tick(&mut ds);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Surrendered));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 950);
+}
+
+#[test]
+fn late_surrender_is_not_offered_against_a_dealer_blackjack() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 11, 6]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    assert_eq!(
+        ds.add_action(hand_id, Action::Surrender),
+        Err(ActionResolutionError::GameNotActive)
+    );
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 900);
+}
+
+// Late surrender at a table without a hole card, the dealer's second card is drawn once the
+// player has surrendered.
+fn late_surrender_without_a_hole_card(dealer_draws: u8) -> (DataSource, Uuid, Uuid) {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules {
+        dealing: Dealing::NoHoleCard {
+            original_bets_only: false,
+        },
+        surrender: Surrender::Late,
+        ..Default::default()
+    });
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 6, dealer_draws]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+    tick(&mut ds);
+
+    ds.add_action(hand_id, Action::Surrender).unwrap();
+    tick(&mut ds);
+    // Nothing is settled until the dealer knows whether they have a natural.
+    assert_eq!(outcome(&ds, hand_id), None);
+    tick(&mut ds);
+    (ds, account_id, hand_id)
+}
+
+#[test]
+fn late_surrender_without_a_hole_card_loses_to_a_dealer_natural() {
+    let (ds, account_id, hand_id) = late_surrender_without_a_hole_card(11);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Lost(16)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 900);
+}
+
+#[test]
+fn late_surrender_without_a_hole_card_returns_half_the_stake() {
+    let (ds, account_id, hand_id) = late_surrender_without_a_hole_card(7);
+    assert_eq!(outcome(&ds, hand_id), Some(Outcome::Surrendered));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 950);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use blackjack::{Action, DataSource, Outcome, TableRules};
+use common::*;
+use uuid::Uuid;
+
+// Sit a single seat down at a Blackjack Switch table and deal it a round, betting on both hands.
+fn deal(cards: &[u8]) -> (DataSource, Uuid, Uuid, [Uuid; 2]) {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game_with_rules(TableRules::switch());
+    ds.set_deck(game_id, loaded_deck(cards));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    let seat_hands = ds.get_seat_hands(hand_id);
+    assert_eq!(seat_hands.len(), 2);
+    for hand_id in &seat_hands {
+        ds.place_bet(*hand_id, 100).unwrap();
+    }
+    tick(&mut ds);
+    (ds, game_id, account_id, [seat_hands[0], seat_hands[1]])
+}
+
+#[test]
+fn switch_swaps_the_second_cards_of_the_seat() {
+    // The first hand holds 10 & 5 and the second 6 & 10, switching makes a 20 and an 11.
+    let (mut ds, game_id, _, [first, second]) = deal(&[9, 10, 6, 8, 5, 10]);
+    let hand_id = current_hand(&ds, game_id).unwrap();
+    ds.add_action(hand_id, Action::Switch).unwrap();
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, first), Some(Outcome::Won(20)));
+    assert_eq!(outcome(&ds, second), Some(Outcome::Lost(11)));
+}
+
+#[test]
+fn natural_only_pays_even_money() {
+    let (mut ds, game_id, account_id, [first, second]) = deal(&[9, 11, 10, 8, 10, 7]);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, first), Some(Outcome::Natural));
+    assert_eq!(outcome(&ds, second), Some(Outcome::Push(17)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1100);
+}
+
+#[test]
+fn dealer_22_pushes_the_standing_hands() {
+    // The dealer draws a 6 onto their 16.
+    let (mut ds, game_id, account_id, [first, second]) = deal(&[10, 10, 10, 6, 8, 9, 6]);
+    stand_all(&mut ds, game_id);
+
+    assert_eq!(outcome(&ds, first), Some(Outcome::Push(18)));
+    assert_eq!(outcome(&ds, second), Some(Outcome::Push(19)));
+    assert_eq!(ds.get_balance(account_id).unwrap().balance, 1000);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use blackjack::{Account, BetError, DataSource, DepositError, Transaction};
+use common::*;
+use uuid::Uuid;
+
+#[test]
+fn stakes_are_reserved_until_the_hand_is_settled() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    ds.set_deck(game_id, loaded_deck(&[10, 10, 9, 8]));
+    let (account_id, hand_id) = seat(&mut ds, game_id);
+    ds.place_bet(hand_id, 100).unwrap();
+
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (1000, 100));
+    assert_eq!(account.available(), 900);
+
+    tick(&mut ds);
+    stand_all(&mut ds, game_id);
+
+    let account = ds.get_balance(account_id).unwrap();
+    assert_eq!((account.balance, account.reserved), (900, 0));
+    let history = ds
+        .transactions
+        .iter()
+        .filter(|t| t.0 == account_id)
+        .map(|t| t.1)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        history,
+        vec![
+            Transaction::Deposit(1000),
+            Transaction::Reserved(hand_id, 100),
+            Transaction::Released(hand_id, 100),
+            Transaction::Lost(hand_id, 100),
+        ]
+    );
+}
+
+#[test]
+fn bets_are_limited_to_the_chips_available() {
+    let mut ds = DataSource::default();
+    let game_id = ds.add_game();
+    let account_id = ds.create_account(50);
+    let hand_id = ds.join_table(game_id, account_id).unwrap();
+
+    assert_eq!(ds.place_bet(hand_id, 60), Err(BetError::InsufficientFunds));
+    ds.deposit(account_id, 50).unwrap();
+    assert_eq!(ds.place_bet(hand_id, 60), Ok(()));
+    assert_eq!(ds.get_balance(account_id).unwrap().available(), 40);
+}
+
+#[test]
+fn deposits_need_an_account() {
+    let mut ds = DataSource::default();
+    assert_eq!(
+        ds.deposit(Uuid::new_v4(), 100),
+        Err(DepositError::MissingAccount)
+    );
+}
+
+#[test]
+fn accounts_never_wrap_around() {
+    let hand_id = Uuid::new_v4();
+    let mut account = Account {
+        balance: 10,
+        reserved: 20,
+    };
+    assert_eq!(account.available(), 0);
+
+    account.apply(Transaction::Lost(hand_id, 50));
+    account.apply(Transaction::Released(hand_id, 50));
+    assert_eq!(
+        account,
+        Account {
+            balance: 0,
+            reserved: 0
+        }
+    );
+
+    account.apply(Transaction::Deposit(u32::MAX));
+    account.apply(Transaction::Won(hand_id, 1));
+    assert_eq!(account.balance, u32::MAX);
+}